@@ -0,0 +1,96 @@
+//! Authoring backoff: authorities skip slots when the best block runs too far ahead of the last
+//! finalized one, so that a stalled finality gadget does not leave an ever-growing unfinalized
+//! chain behind.
+
+use node_template_runtime::BlockNumber;
+use sc_consensus_slots::{BackoffAuthoringBlocksStrategy, BackoffAuthoringOnFinalizedHeadLagging};
+use sp_consensus_slots::Slot;
+use structopt::StructOpt;
+use substrate_prometheus_endpoint::{register, Counter, PrometheusError, Registry, U64};
+
+#[cfg(test)]
+mod tests;
+
+/// Parameters of the authoring backoff.
+#[derive(Debug, Clone, StructOpt)]
+pub struct BackoffParams {
+	/// Skip authoring slots while finality lags behind the best block.
+	#[structopt(long)]
+	pub backoff_authoring: bool,
+
+	/// The maximum number of slots skipped in a row.
+	#[structopt(long, default_value = "100")]
+	pub backoff_max_interval: BlockNumber,
+
+	/// How many unfinalized blocks are tolerated before authoring backs off.
+	#[structopt(long, default_value = "50")]
+	pub backoff_unfinalized_slack: BlockNumber,
+
+	/// How many unfinalized blocks beyond the slack add one more skipped slot.
+	#[structopt(long, default_value = "2")]
+	pub backoff_authoring_bias: BlockNumber,
+}
+
+impl BackoffParams {
+	/// The backoff strategy, if enabled.
+	pub fn strategy(
+		&self,
+		registry: Option<&Registry>,
+	) -> Result<Option<MeteredBackoff>, PrometheusError> {
+		if !self.backoff_authoring {
+			return Ok(None)
+		}
+
+		let skipped_slots = registry
+			.map(|registry| {
+				register(
+					Counter::new(
+						"node_authoring_backoff_skipped_slots_total",
+						"Number of slots authoring was skipped in because finality lagged",
+					)?,
+					registry,
+				)
+			})
+			.transpose()?;
+
+		Ok(Some(MeteredBackoff {
+			inner: BackoffAuthoringOnFinalizedHeadLagging {
+				max_interval: self.backoff_max_interval,
+				unfinalized_slack: self.backoff_unfinalized_slack,
+				authoring_bias: self.backoff_authoring_bias,
+			},
+			skipped_slots,
+		}))
+	}
+}
+
+/// [`BackoffAuthoringOnFinalizedHeadLagging`], counting the slots it skips.
+pub struct MeteredBackoff {
+	inner: BackoffAuthoringOnFinalizedHeadLagging<BlockNumber>,
+	skipped_slots: Option<Counter<U64>>,
+}
+
+impl BackoffAuthoringBlocksStrategy<BlockNumber> for MeteredBackoff {
+	fn should_backoff(
+		&self,
+		chain_head_number: BlockNumber,
+		chain_head_slot: Slot,
+		finalized_number: BlockNumber,
+		slot_now: Slot,
+		logging_target: &str,
+	) -> bool {
+		let backoff = self.inner.should_backoff(
+			chain_head_number,
+			chain_head_slot,
+			finalized_number,
+			slot_now,
+			logging_target,
+		);
+		if backoff {
+			if let Some(skipped_slots) = &self.skipped_slots {
+				skipped_slots.inc();
+			}
+		}
+		backoff
+	}
+}
@@ -0,0 +1,58 @@
+use super::BackoffParams;
+use crate::test_utils::parse_args;
+use sc_consensus_slots::BackoffAuthoringBlocksStrategy;
+use substrate_prometheus_endpoint::Registry;
+
+#[test]
+fn backoff_is_disabled_by_default() {
+	assert!(parse_args::<BackoffParams>(&[]).strategy(None).unwrap().is_none());
+}
+
+#[test]
+fn defaults_map_onto_the_strategy() {
+	let strategy = parse_args::<BackoffParams>(&["--backoff-authoring"])
+		.strategy(None)
+		.unwrap()
+		.unwrap();
+	assert_eq!(strategy.inner.max_interval, 100);
+	assert_eq!(strategy.inner.unfinalized_slack, 50);
+	assert_eq!(strategy.inner.authoring_bias, 2);
+	assert!(strategy.skipped_slots.is_none());
+}
+
+#[test]
+fn options_map_onto_the_strategy() {
+	let strategy = parse_args::<BackoffParams>(&[
+		"--backoff-authoring",
+		"--backoff-max-interval",
+		"10",
+		"--backoff-unfinalized-slack",
+		"20",
+		"--backoff-authoring-bias",
+		"3",
+	])
+	.strategy(None)
+	.unwrap()
+	.unwrap();
+	assert_eq!(strategy.inner.max_interval, 10);
+	assert_eq!(strategy.inner.unfinalized_slack, 20);
+	assert_eq!(strategy.inner.authoring_bias, 3);
+}
+
+#[test]
+fn skipped_slots_are_counted() {
+	let registry = Registry::new();
+	let strategy = parse_args::<BackoffParams>(&["--backoff-authoring"])
+		.strategy(Some(&registry))
+		.unwrap()
+		.unwrap();
+
+	// 200 unfinalized blocks are 150 beyond the slack: authoring backs off for 75 slots.
+	assert!(strategy.should_backoff(200, 100.into(), 0, 101.into(), "test"));
+	assert!(strategy.should_backoff(200, 100.into(), 0, 175.into(), "test"));
+	assert!(!strategy.should_backoff(200, 100.into(), 0, 176.into(), "test"));
+	// Within the slack, authoring goes on.
+	assert!(!strategy.should_backoff(50, 100.into(), 0, 101.into(), "test"));
+
+	assert_eq!(strategy.skipped_slots.as_ref().unwrap().get(), 2);
+}
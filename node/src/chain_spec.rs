@@ -1,18 +1,91 @@
+#[cfg(not(feature = "babe"))]
+use node_template_runtime::AuraConfig;
 use node_template_runtime::{
-	AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig, Signature, SudoConfig,
-	SystemConfig, WASM_BINARY,
+	opaque::SessionKeys, AccountId, AssetId, AssetsConfig, AuthorityDiscoveryConfig, Balance,
+	BalancesConfig, BlockNumber, GenesisConfig, GrandpaConfig, ImOnlineConfig, IndicesConfig,
+	SS58Prefix, SessionConfig, Signature, StakerStatus, StakingConfig, SudoConfig, SystemConfig,
+	VestingConfig, TOKEN_DECIMALS, TOKEN_SYMBOL, WASM_BINARY,
 };
-use sc_service::ChainType;
-use sp_consensus_aura::sr25519::AuthorityId as AuraId;
-use sp_core::{sr25519, Pair, Public};
+#[cfg(feature = "babe")]
+use node_template_runtime::{BabeConfig, BABE_GENESIS_EPOCH_CONFIG};
+use pallet_im_online::sr25519::AuthorityId as ImOnlineId;
+use sc_chain_spec::{ChainSpecExtension, ChainSpecGroup};
+use sc_service::{ChainType, Properties};
+use serde::{Deserialize, Serialize};
+use sp_authority_discovery::AuthorityId as AuthorityDiscoveryId;
+use sp_core::{sr25519, Get, Pair, Public};
 use sp_finality_grandpa::AuthorityId as GrandpaId;
-use sp_runtime::traits::{IdentifyAccount, Verify};
+use sp_runtime::{
+	traits::{IdentifyAccount, Verify},
+	Perbill,
+};
 
 // The URL for the telemetry server.
 // const STAGING_TELEMETRY_URL: &str = "wss://telemetry.polkadot.io/submit/";
 
+/// Node parameters carried by the chain spec, which every node of a chain should share.
+#[derive(Default, Clone, Debug, Serialize, Deserialize, ChainSpecExtension, ChainSpecGroup)]
+#[serde(rename_all = "camelCase")]
+pub struct Extensions {
+	/// GRANDPA parameters. Defaults apply to specs without them.
+	#[serde(default)]
+	pub finality: FinalityParams,
+}
+
+impl Extensions {
+	/// The extensions of `chain_spec`, if it is a spec of this chain.
+	pub fn try_get(chain_spec: &dyn sc_service::ChainSpec) -> Option<&Self> {
+		sc_chain_spec::get_extension(chain_spec.extensions())
+	}
+}
+
+/// Parameters of the GRANDPA finality gadget.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FinalityParams {
+	/// How often votes are gossiped to peers, in milliseconds.
+	pub gossip_duration: u64,
+	/// A justification of finality is stored every this many blocks.
+	pub justification_period: u32,
+	/// Voters never vote on blocks less than this many blocks behind the best one, on top of the
+	/// default voting rules.
+	pub finality_delay: BlockNumber,
+}
+
+impl Default for FinalityParams {
+	fn default() -> Self {
+		Self { gossip_duration: 333, justification_period: 512, finality_delay: 0 }
+	}
+}
+
 /// Specialized `ChainSpec`. This is a specialization of the general Substrate ChainSpec type.
-pub type ChainSpec = sc_service::GenericChainSpec<GenesisConfig>;
+pub type ChainSpec = sc_service::GenericChainSpec<GenesisConfig, Extensions>;
+
+/// The properties wallets read the native token and the address format from, as set by the runtime.
+pub fn chain_properties() -> Properties {
+	let mut properties = Properties::new();
+	properties.insert("tokenSymbol".into(), TOKEN_SYMBOL.into());
+	properties.insert("tokenDecimals".into(), TOKEN_DECIMALS.into());
+	properties.insert("ss58Format".into(), SS58Prefix::get().into());
+	properties
+}
+
+/// Warn about every property of `chain_spec` that disagrees with the runtime, e.g. in a spec
+/// written by hand or for an older runtime.
+pub fn check_properties(chain_spec: &dyn sc_service::ChainSpec) {
+	let properties = chain_spec.properties();
+	for (key, expected) in chain_properties() {
+		match properties.get(&key) {
+			Some(value) if *value == expected => {},
+			value => log::warn!(
+				"Chain spec property `{}` is {}, but the runtime uses {}",
+				key,
+				value.map_or_else(|| "missing".into(), |v| v.to_string()),
+				expected,
+			),
+		}
+	}
+}
 
 /// Generate a crypto pair from seed.
 pub fn get_from_seed<TPublic: Public>(seed: &str) -> <TPublic::Pair as Pair>::Public {
@@ -31,9 +104,68 @@ where
 	AccountPublic::from(get_from_seed::<TPublic>(seed)).into_account()
 }
 
-/// Generate an Aura authority key.
-pub fn authority_keys_from_seed(s: &str) -> (AuraId, GrandpaId) {
-	(get_from_seed::<AuraId>(s), get_from_seed::<GrandpaId>(s))
+/// A fungible asset created at genesis: its id, owner, minimum balance and initial holdings.
+pub struct GenesisAsset {
+	pub id: AssetId,
+	pub owner: AccountId,
+	pub min_balance: Balance,
+	pub name: Vec<u8>,
+	pub symbol: Vec<u8>,
+	pub decimals: u8,
+	pub accounts: Vec<(AccountId, Balance)>,
+}
+
+/// A vesting schedule applied at genesis: the account, the block vesting begins at, the number
+/// of blocks over which it vests and the amount that is liquid from the start.
+pub type GenesisVesting = (AccountId, BlockNumber, BlockNumber, Balance);
+
+/// The key used to author blocks: Aura's, or BABE's when built with the `babe` feature.
+#[cfg(not(feature = "babe"))]
+pub type AuthorshipId = sp_consensus_aura::sr25519::AuthorityId;
+/// The key used to author blocks: Aura's, or BABE's when built with the `babe` feature.
+#[cfg(feature = "babe")]
+pub type AuthorshipId = sp_consensus_babe::AuthorityId;
+
+/// The balance every endowed account starts with.
+const ENDOWMENT: Balance = 1 << 60;
+/// The amount each initial validator bonds from its stash.
+pub(crate) const STASH: Balance = ENDOWMENT / 1000;
+
+/// The stash and controller accounts of a validator, followed by its session keys.
+pub type AuthorityKeys =
+	(AccountId, AccountId, AuthorshipId, GrandpaId, ImOnlineId, AuthorityDiscoveryId);
+
+/// Generate the stash and controller accounts, and the session keys, of a validator. The stash is
+/// derived from `<seed>//stash`.
+pub fn authority_keys_from_seed(s: &str) -> AuthorityKeys {
+	(
+		get_account_id_from_seed::<sr25519::Public>(&format!("{}//stash", s)),
+		get_account_id_from_seed::<sr25519::Public>(s),
+		get_from_seed::<AuthorshipId>(s),
+		get_from_seed::<GrandpaId>(s),
+		get_from_seed::<ImOnlineId>(s),
+		get_from_seed::<AuthorityDiscoveryId>(s),
+	)
+}
+
+#[cfg(not(feature = "babe"))]
+fn session_keys(
+	authorship: AuthorshipId,
+	grandpa: GrandpaId,
+	im_online: ImOnlineId,
+	authority_discovery: AuthorityDiscoveryId,
+) -> SessionKeys {
+	SessionKeys { aura: authorship, grandpa, im_online, authority_discovery }
+}
+
+#[cfg(feature = "babe")]
+fn session_keys(
+	authorship: AuthorshipId,
+	grandpa: GrandpaId,
+	im_online: ImOnlineId,
+	authority_discovery: AuthorityDiscoveryId,
+) -> SessionKeys {
+	SessionKeys { babe: authorship, grandpa, im_online, authority_discovery }
 }
 
 pub fn development_config() -> Result<ChainSpec, String> {
@@ -48,7 +180,7 @@ pub fn development_config() -> Result<ChainSpec, String> {
 		move || {
 			testnet_genesis(
 				wasm_binary,
-				// Initial PoA authorities
+				// Initial validators
 				vec![authority_keys_from_seed("Alice")],
 				// Sudo account
 				get_account_id_from_seed::<sr25519::Public>("Alice"),
@@ -58,7 +190,14 @@ pub fn development_config() -> Result<ChainSpec, String> {
 					get_account_id_from_seed::<sr25519::Public>("Bob"),
 					get_account_id_from_seed::<sr25519::Public>("Alice//stash"),
 					get_account_id_from_seed::<sr25519::Public>("Bob//stash"),
-				],
+				]
+				.into_iter()
+				.map(|k| (k, ENDOWMENT))
+				.collect(),
+				// Genesis assets
+				vec![],
+				// Genesis vesting schedules
+				vec![],
 				true,
 			)
 		},
@@ -69,9 +208,9 @@ pub fn development_config() -> Result<ChainSpec, String> {
 		// Protocol ID
 		None,
 		// Properties
-		None,
+		Some(chain_properties()),
 		// Extensions
-		None,
+		Default::default(),
 	))
 }
 
@@ -87,7 +226,7 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 		move || {
 			testnet_genesis(
 				wasm_binary,
-				// Initial PoA authorities
+				// Initial validators
 				vec![authority_keys_from_seed("Alice"), authority_keys_from_seed("Bob")],
 				// Sudo account
 				get_account_id_from_seed::<sr25519::Public>("Alice"),
@@ -105,7 +244,14 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 					get_account_id_from_seed::<sr25519::Public>("Dave//stash"),
 					get_account_id_from_seed::<sr25519::Public>("Eve//stash"),
 					get_account_id_from_seed::<sr25519::Public>("Ferdie//stash"),
-				],
+				]
+				.into_iter()
+				.map(|k| (k, ENDOWMENT))
+				.collect(),
+				// Genesis assets
+				vec![],
+				// Genesis vesting schedules
+				vec![],
 				true,
 			)
 		},
@@ -116,18 +262,20 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 		// Protocol ID
 		None,
 		// Properties
-		None,
+		Some(chain_properties()),
 		// Extensions
-		None,
+		Default::default(),
 	))
 }
 
 /// Configure initial storage state for FRAME modules.
-fn testnet_genesis(
+pub(crate) fn testnet_genesis(
 	wasm_binary: &[u8],
-	initial_authorities: Vec<(AuraId, GrandpaId)>,
+	initial_authorities: Vec<AuthorityKeys>,
 	root_key: AccountId,
-	endowed_accounts: Vec<AccountId>,
+	endowed_accounts: Vec<(AccountId, Balance)>,
+	initial_assets: Vec<GenesisAsset>,
+	vesting: Vec<GenesisVesting>,
 	_enable_println: bool,
 ) -> GenesisConfig {
 	GenesisConfig {
@@ -137,15 +285,55 @@ fn testnet_genesis(
 			changes_trie_config: Default::default(),
 		},
 		balances: BalancesConfig {
-			// Configure endowed accounts with initial balance of 1 << 60.
-			balances: endowed_accounts.iter().cloned().map(|k| (k, 1 << 60)).collect(),
+			// Configure endowed accounts with their initial balances.
+			balances: endowed_accounts,
+		},
+		indices: IndicesConfig { indices: vec![] },
+		staking: StakingConfig {
+			validator_count: initial_authorities.len() as u32,
+			minimum_validator_count: initial_authorities.len() as u32,
+			stakers: initial_authorities
+				.iter()
+				.map(|x| (x.0.clone(), x.1.clone(), STASH, StakerStatus::Validator))
+				.collect(),
+			invulnerables: initial_authorities.iter().map(|x| x.0.clone()).collect(),
+			slash_reward_fraction: Perbill::from_percent(10),
+			..Default::default()
 		},
-		aura: AuraConfig {
-			authorities: initial_authorities.iter().map(|x| (x.0.clone())).collect(),
+		session: SessionConfig {
+			keys: initial_authorities
+				.iter()
+				.map(|x| {
+					let keys = session_keys(x.2.clone(), x.3.clone(), x.4.clone(), x.5.clone());
+					(x.0.clone(), x.0.clone(), keys)
+				})
+				.collect(),
 		},
-		grandpa: GrandpaConfig {
-			authorities: initial_authorities.iter().map(|x| (x.1.clone(), 1)).collect(),
+		// The consensus, im-online and authority discovery keys are set from the session keys above.
+		#[cfg(not(feature = "babe"))]
+		aura: AuraConfig { authorities: vec![] },
+		#[cfg(feature = "babe")]
+		babe: BabeConfig { authorities: vec![], epoch_config: Some(BABE_GENESIS_EPOCH_CONFIG) },
+		grandpa: GrandpaConfig { authorities: vec![] },
+		im_online: ImOnlineConfig { keys: vec![] },
+		authority_discovery: AuthorityDiscoveryConfig { keys: vec![] },
+		assets: AssetsConfig {
+			assets: initial_assets
+				.iter()
+				.map(|a| (a.id, a.owner.clone(), true, a.min_balance))
+				.collect(),
+			metadata: initial_assets
+				.iter()
+				.map(|a| (a.id, a.name.clone(), a.symbol.clone(), a.decimals))
+				.collect(),
+			accounts: initial_assets
+				.iter()
+				.flat_map(|a| {
+					a.accounts.iter().map(move |(who, amount)| (a.id, who.clone(), *amount))
+				})
+				.collect(),
 		},
+		vesting: VestingConfig { vesting },
 		sudo: SudoConfig {
 			// Assign network admin rights.
 			key: root_key,
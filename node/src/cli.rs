@@ -1,3 +1,11 @@
+use crate::{
+	backoff::BackoffParams,
+	generate_spec::GenerateSpecCmd,
+	health::HealthParams,
+	indexer::IndexerParams,
+	remote_keystore::{signer::SignerCmd, RemoteKeystoreParams},
+	sealing::Sealing,
+};
 use sc_cli::RunCmd;
 use structopt::StructOpt;
 
@@ -8,6 +16,24 @@ pub struct Cli {
 
 	#[structopt(flatten)]
 	pub run: RunCmd,
+
+	/// Seal blocks with manual-seal instead of running Aura (or BABE) and GRANDPA: `instant`
+	/// seals a block whenever a transaction is ready, `manual` only when requested over the
+	/// `engine_createBlock` RPC, and a number of milliseconds seals blocks at that interval.
+	#[structopt(long)]
+	pub sealing: Option<Sealing>,
+
+	#[structopt(flatten)]
+	pub backoff: BackoffParams,
+
+	#[structopt(flatten)]
+	pub indexer: IndexerParams,
+
+	#[structopt(flatten)]
+	pub health: HealthParams,
+
+	#[structopt(flatten)]
+	pub remote_keystore: RemoteKeystoreParams,
 }
 
 #[derive(Debug, StructOpt)]
@@ -17,6 +43,9 @@ pub enum Subcommand {
 	/// Build a chain specification.
 	BuildSpec(sc_cli::BuildSpecCmd),
 
+	/// Generate a chain specification from a TOML or JSON network description.
+	GenerateSpec(GenerateSpecCmd),
+
 	/// Validate blocks.
 	CheckBlock(sc_cli::CheckBlockCmd),
 
@@ -35,7 +64,18 @@ pub enum Subcommand {
 	/// Revert the chain to a previous state.
 	Revert(sc_cli::RevertCmd),
 
+	/// Serve the keys of a keystore to nodes started with `--keystore-uri`.
+	RemoteSigner(SignerCmd),
+
 	/// The custom benchmark subcommmand benchmarking runtime pallets.
 	#[structopt(name = "benchmark", about = "Benchmark runtime pallets.")]
 	Benchmark(frame_benchmarking_cli::BenchmarkCmd),
+
+	/// Try some command against runtime state.
+	#[cfg(feature = "try-runtime")]
+	TryRuntime(try_runtime_cli::TryRuntimeCmd),
+
+	/// Try some command against runtime state. Note: `try-runtime` feature must be enabled.
+	#[cfg(not(feature = "try-runtime"))]
+	TryRuntime,
 }
@@ -21,7 +21,7 @@ use crate::{
 	service,
 };
 use node_template_runtime::Block;
-use sc_cli::{ChainSpec, Role, RuntimeVersion, SubstrateCli};
+use sc_cli::{ChainSpec, CliConfiguration, Role, RuntimeVersion, SubstrateCli};
 use sc_service::PartialComponents;
 
 impl SubstrateCli for Cli {
@@ -73,6 +73,7 @@ pub fn run() -> sc_cli::Result<()> {
 			let runner = cli.create_runner(cmd)?;
 			runner.sync_run(|config| cmd.run(config.chain_spec, config.network))
 		},
+		Some(Subcommand::GenerateSpec(cmd)) => cmd.run(),
 		Some(Subcommand::CheckBlock(cmd)) => {
 			let runner = cli.create_runner(cmd)?;
 			runner.async_run(|config| {
@@ -115,6 +116,10 @@ pub fn run() -> sc_cli::Result<()> {
 				Ok((cmd.run(client, backend), task_manager))
 			})
 		},
+		Some(Subcommand::RemoteSigner(cmd)) => {
+			cmd.init::<Cli>()?;
+			cmd.run()
+		},
 		Some(Subcommand::Benchmark(cmd)) =>
 			if cfg!(feature = "runtime-benchmarks") {
 				let runner = cli.create_runner(cmd)?;
@@ -125,15 +130,67 @@ pub fn run() -> sc_cli::Result<()> {
 				You can enable it with `--features runtime-benchmarks`."
 					.into())
 			},
+		#[cfg(feature = "try-runtime")]
+		Some(Subcommand::TryRuntime(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.async_run(|config| {
+				// The command only needs a task manager to drive the remote externalities builder,
+				// not a full service.
+				let registry = config.prometheus_config.as_ref().map(|cfg| &cfg.registry);
+				let task_manager =
+					sc_service::TaskManager::new(config.task_executor.clone(), registry)
+						.map_err(|e| sc_cli::Error::Service(sc_service::Error::Prometheus(e)))?;
+				Ok((cmd.run::<Block, service::Executor>(config), task_manager))
+			})
+		},
+		#[cfg(not(feature = "try-runtime"))]
+		Some(Subcommand::TryRuntime) => Err("TryRuntime wasn't enabled when building the node. \
+				You can enable it with `--features try-runtime`."
+			.into()),
 		None => {
+			if cli.sealing.is_some() {
+				let unused = unused_with_sealing(&cli);
+				if !unused.is_empty() {
+					return Err(
+						format!("{} cannot be used with `--sealing`", unused.join(", ")).into()
+					)
+				}
+			}
+
 			let runner = cli.create_runner(&cli.run)?;
+			let sealing = cli.sealing;
+			let backoff = cli.backoff.clone();
+			let indexer = cli.indexer.clone();
+			let health = cli.health.clone();
+			let keystore_params = cli.remote_keystore.clone();
 			runner.run_node_until_exit(|config| async move {
-				match config.role {
-					Role::Light => service::new_light(config),
-					_ => service::new_full(config),
+				chain_spec::check_properties(&*config.chain_spec);
+
+				match (config.role.clone(), sealing) {
+					(Role::Light, _) => service::new_light(config),
+					(_, Some(sealing)) => service::new_manual_seal(config, sealing, indexer),
+					_ => service::new_full(config, backoff, indexer, health, keystore_params),
 				}
 				.map_err(sc_cli::Error::Service)
 			})
 		},
 	}
 }
+
+/// The options given that manual-seal nodes do not support.
+fn unused_with_sealing(cli: &Cli) -> Vec<&'static str> {
+	let mut unused = Vec::new();
+	if cli.backoff.backoff_authoring {
+		unused.push("`--backoff-authoring`");
+	}
+	if cli.health.health_port.is_some() {
+		unused.push("`--health-port`");
+	}
+	if cli.run.keystore_params.keystore_uri.is_some() {
+		unused.push("`--keystore-uri`");
+	}
+	if cli.remote_keystore.keystore_token_file.is_some() {
+		unused.push("`--keystore-token-file`");
+	}
+	unused
+}
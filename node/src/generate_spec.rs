@@ -0,0 +1,277 @@
+//! The `generate-spec` subcommand, which builds a chain spec from a network description instead
+//! of a chain spec defined in `chain_spec.rs`.
+
+use crate::chain_spec::{
+	authority_keys_from_seed, chain_properties, get_account_id_from_seed, testnet_genesis,
+	AuthorityKeys, ChainSpec, STASH,
+};
+use node_template_runtime::{AccountId, Balance, ExistentialDeposit, WASM_BINARY};
+use sc_service::{config::TelemetryEndpoints, ChainType, Properties};
+use serde::{de::Error as _, Deserialize, Deserializer};
+use sp_core::{crypto::Ss58Codec, sr25519};
+use std::{collections::BTreeSet, fs, io::Write, path::PathBuf};
+use structopt::StructOpt;
+
+#[cfg(test)]
+mod tests;
+
+/// The `generate-spec` command.
+#[derive(Debug, StructOpt)]
+pub struct GenerateSpecCmd {
+	/// The network description: a TOML file, or a JSON one if its extension is `.json`.
+	#[structopt(parse(from_os_str))]
+	pub description: PathBuf,
+
+	/// Output the genesis storage as raw key-value pairs, as nodes load it.
+	#[structopt(long)]
+	pub raw: bool,
+}
+
+impl GenerateSpecCmd {
+	/// Print the chain spec of the described network to stdout.
+	pub fn run(&self) -> sc_cli::Result<()> {
+		let contents = fs::read_to_string(&self.description)?;
+		let description: NetworkDescription =
+			if self.description.extension().map_or(false, |e| e == "json") {
+				serde_json::from_str(&contents).map_err(|e| e.to_string())?
+			} else {
+				toml::from_str(&contents).map_err(|e| e.to_string())?
+			};
+
+		let chain_spec = description.into_chain_spec()?;
+		let json = sc_service::chain_ops::build_spec(&chain_spec, self.raw)?;
+		std::io::stdout().write_all(json.as_bytes())?;
+		Ok(())
+	}
+}
+
+/// A network to generate a chain spec for.
+///
+/// Accounts are SS58 addresses, or development seeds such as `//Alice` derived the way the
+/// built-in chain specs derive them.
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NetworkDescription {
+	/// The human-readable name of the chain.
+	pub name: String,
+	/// The id of the chain, which also names its database directory.
+	pub id: String,
+	/// `Development`, `Local` or `Live`.
+	pub chain_type: ChainType,
+	/// The initial validators, which may not share stashes, controllers or session keys.
+	pub authorities: Vec<AuthorityDescription>,
+	/// The accounts endowed at genesis, each once and with at least the existential deposit.
+	/// Every validator's stash must be one of them.
+	pub endowed: Vec<EndowedAccount>,
+	/// The sudo key.
+	pub sudo: String,
+	/// Multiaddresses of the nodes new nodes connect to first.
+	#[serde(default)]
+	pub bootnodes: Vec<String>,
+	/// Telemetry endpoints and their verbosity.
+	#[serde(default)]
+	pub telemetry: Vec<(String, u8)>,
+	/// The network protocol id.
+	pub protocol_id: Option<String>,
+	/// Chain properties, which default to the ones the runtime sets.
+	pub properties: Option<Properties>,
+}
+
+/// A validator, either derived from a development seed or given key by key.
+///
+/// Unknown fields are rejected, so that a description mixing both forms matches neither.
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(untagged, deny_unknown_fields)]
+pub enum AuthorityDescription {
+	/// Accounts and keys derived from a seed such as `Alice`, as for the built-in chain specs.
+	/// Unlike accounts, the seed is written without a leading `//`.
+	Seed { seed: String },
+	/// Accounts and session keys given as SS58 addresses.
+	Keys {
+		stash: String,
+		controller: String,
+		authorship: String,
+		grandpa: String,
+		im_online: String,
+		authority_discovery: String,
+	},
+}
+
+/// An account endowed at genesis.
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EndowedAccount {
+	/// The account.
+	pub account: String,
+	/// Its free balance, as an integer or, if too large for the description format, a string.
+	#[serde(deserialize_with = "deserialize_balance")]
+	pub balance: Balance,
+}
+
+impl NetworkDescription {
+	/// Build the chain spec of the described network with [`testnet_genesis`].
+	pub fn into_chain_spec(self) -> Result<ChainSpec, String> {
+		let wasm_binary = WASM_BINARY.ok_or_else(|| "Wasm binary not available".to_string())?;
+
+		if self.authorities.is_empty() {
+			return Err("At least one authority is required".into())
+		}
+		let authorities = self
+			.authorities
+			.into_iter()
+			.map(AuthorityDescription::into_keys)
+			.collect::<Result<Vec<_>, _>>()?;
+		let endowed = self
+			.endowed
+			.into_iter()
+			.map(|e| Ok((parse_account(&e.account)?, e.balance)))
+			.collect::<Result<Vec<_>, String>>()?;
+		let sudo = parse_account(&self.sudo)?;
+
+		// Genesis storage is built by assertions, which would panic on these instead of erroring.
+		check_endowed(&endowed)?;
+		check_authorities(&authorities)?;
+
+		// Validators bond `STASH` from their stash at genesis.
+		for (stash, ..) in &authorities {
+			if !endowed.iter().any(|(who, balance)| who == stash && *balance >= STASH) {
+				return Err(format!("Stash {} must be endowed with at least {}", stash, STASH))
+			}
+		}
+
+		let boot_nodes = self
+			.bootnodes
+			.iter()
+			.map(|addr| addr.parse().map_err(|e| format!("Invalid bootnode {}: {}", addr, e)))
+			.collect::<Result<Vec<_>, _>>()?;
+		let telemetry = if self.telemetry.is_empty() {
+			None
+		} else {
+			Some(TelemetryEndpoints::new(self.telemetry).map_err(|e| e.to_string())?)
+		};
+		let protocol_id = self.protocol_id;
+
+		Ok(ChainSpec::from_genesis(
+			&self.name,
+			&self.id,
+			self.chain_type,
+			move || {
+				testnet_genesis(
+					wasm_binary,
+					authorities.clone(),
+					sudo.clone(),
+					endowed.clone(),
+					vec![],
+					vec![],
+					true,
+				)
+			},
+			boot_nodes,
+			telemetry,
+			protocol_id.as_deref(),
+			Some(self.properties.unwrap_or_else(chain_properties)),
+			Default::default(),
+		))
+	}
+}
+
+impl AuthorityDescription {
+	fn into_keys(self) -> Result<AuthorityKeys, String> {
+		Ok(match self {
+			Self::Seed { seed } if seed.starts_with('/') =>
+				return Err(format!(
+					"Invalid authority seed {}: authority seeds are written without the leading \
+					 `//`, e.g. `Alice`",
+					seed
+				)),
+			Self::Seed { seed } => authority_keys_from_seed(&seed),
+			Self::Keys {
+				stash,
+				controller,
+				authorship,
+				grandpa,
+				im_online,
+				authority_discovery,
+			} => (
+				parse_account(&stash)?,
+				parse_account(&controller)?,
+				parse_key(&authorship)?,
+				parse_key(&grandpa)?,
+				parse_key(&im_online)?,
+				parse_key(&authority_discovery)?,
+			),
+		})
+	}
+}
+
+/// Check that no account is endowed twice or with less than the existential deposit.
+fn check_endowed(endowed: &[(AccountId, Balance)]) -> Result<(), String> {
+	let mut accounts = BTreeSet::new();
+	for (who, balance) in endowed {
+		if !accounts.insert(who) {
+			return Err(format!("Account {} is endowed more than once", who))
+		}
+		if *balance < ExistentialDeposit::get() {
+			return Err(format!(
+				"Account {} must be endowed with at least the existential deposit of {}",
+				who,
+				ExistentialDeposit::get()
+			))
+		}
+	}
+	Ok(())
+}
+
+/// Check that no two authorities share a stash, a controller or a session key.
+fn check_authorities(authorities: &[AuthorityKeys]) -> Result<(), String> {
+	let mut used = BTreeSet::new();
+	for (stash, controller, authorship, grandpa, im_online, authority_discovery) in authorities {
+		let accounts_and_keys = [
+			("stash", stash.to_ss58check()),
+			("controller", controller.to_ss58check()),
+			("authorship key", authorship.to_ss58check()),
+			("grandpa key", grandpa.to_ss58check()),
+			("im_online key", im_online.to_ss58check()),
+			("authority_discovery key", authority_discovery.to_ss58check()),
+		];
+		for (kind, value) in accounts_and_keys.iter() {
+			if !used.insert((*kind, value.clone())) {
+				return Err(format!("The {} {} is used by more than one authority", kind, value))
+			}
+		}
+	}
+	Ok(())
+}
+
+/// Parse an SS58 address, or derive a development account from a seed starting with `//`.
+fn parse_account(account: &str) -> Result<AccountId, String> {
+	match account.strip_prefix("//") {
+		Some(seed) => Ok(get_account_id_from_seed::<sr25519::Public>(seed)),
+		None => AccountId::from_ss58check(account).map_err(|e| {
+			format!(
+				"Invalid account {}: {:?}. Development accounts are written with a leading `//`, \
+				 e.g. `//Alice`",
+				account, e
+			)
+		}),
+	}
+}
+
+/// Parse a public session key given as an SS58 address.
+fn parse_key<K: Ss58Codec>(key: &str) -> Result<K, String> {
+	K::from_ss58check(key).map_err(|e| format!("Invalid key {}: {:?}", key, e))
+}
+
+fn deserialize_balance<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Balance, D::Error> {
+	#[derive(Deserialize)]
+	#[serde(untagged)]
+	enum Amount {
+		Integer(u64),
+		String(String),
+	}
+
+	match Amount::deserialize(deserializer)? {
+		Amount::Integer(balance) => Ok(balance.into()),
+		Amount::String(balance) => balance.parse().map_err(D::Error::custom),
+	}
+}
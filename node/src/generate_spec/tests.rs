@@ -0,0 +1,167 @@
+use super::{AuthorityDescription, NetworkDescription};
+use crate::chain_spec::{authority_keys_from_seed, ChainSpec, STASH};
+use sc_service::ChainType;
+use sp_core::crypto::Ss58Codec;
+
+/// Bob's keys, given key by key.
+fn bob_keys_toml() -> String {
+	let (stash, controller, authorship, grandpa, im_online, authority_discovery) =
+		authority_keys_from_seed("Bob");
+	format!(
+		"[[authorities]]\n\
+		 stash = \"{}\"\n\
+		 controller = \"{}\"\n\
+		 authorship = \"{}\"\n\
+		 grandpa = \"{}\"\n\
+		 im_online = \"{}\"\n\
+		 authority_discovery = \"{}\"\n",
+		stash.to_ss58check(),
+		controller.to_ss58check(),
+		authorship.to_ss58check(),
+		grandpa.to_ss58check(),
+		im_online.to_ss58check(),
+		authority_discovery.to_ss58check(),
+	)
+}
+
+/// A network of Alice, derived from her seed, and Bob, given key by key.
+fn description_toml() -> String {
+	format!(
+		"name = \"Test\"\n\
+		 id = \"test\"\n\
+		 chain_type = \"Local\"\n\
+		 sudo = \"//Alice\"\n\
+		 [[authorities]]\n\
+		 seed = \"Alice\"\n\
+		 {}\
+		 [[endowed]]\n\
+		 account = \"//Alice//stash\"\n\
+		 balance = \"{stash}\"\n\
+		 [[endowed]]\n\
+		 account = \"//Bob//stash\"\n\
+		 balance = \"{stash}\"\n",
+		bob_keys_toml(),
+		stash = STASH,
+	)
+}
+
+fn description_json() -> String {
+	let (stash, controller, authorship, grandpa, im_online, authority_discovery) =
+		authority_keys_from_seed("Bob");
+	serde_json::json!({
+		"name": "Test",
+		"id": "test",
+		"chain_type": "Local",
+		"sudo": "//Alice",
+		"authorities": [
+			{ "seed": "Alice" },
+			{
+				"stash": stash.to_ss58check(),
+				"controller": controller.to_ss58check(),
+				"authorship": authorship.to_ss58check(),
+				"grandpa": grandpa.to_ss58check(),
+				"im_online": im_online.to_ss58check(),
+				"authority_discovery": authority_discovery.to_ss58check(),
+			},
+		],
+		"endowed": [
+			{ "account": "//Alice//stash", "balance": STASH.to_string() },
+			{ "account": "//Bob//stash", "balance": STASH.to_string() },
+		],
+	})
+	.to_string()
+}
+
+#[test]
+fn toml_and_json_describe_the_same_network() {
+	let toml: NetworkDescription = toml::from_str(&description_toml()).unwrap();
+	let json: NetworkDescription = serde_json::from_str(&description_json()).unwrap();
+	assert_eq!(toml, json);
+
+	assert_eq!(toml.chain_type, ChainType::Local);
+	assert!(matches!(&toml.authorities[0], AuthorityDescription::Seed { seed } if seed == "Alice"));
+	assert!(matches!(toml.authorities[1], AuthorityDescription::Keys { .. }));
+}
+
+#[test]
+fn both_authority_forms_give_the_same_keys() {
+	let description: NetworkDescription = toml::from_str(&description_toml()).unwrap();
+	let mut authorities = description.authorities.into_iter();
+	assert_eq!(authorities.next().unwrap().into_keys().unwrap(), authority_keys_from_seed("Alice"));
+	assert_eq!(authorities.next().unwrap().into_keys().unwrap(), authority_keys_from_seed("Bob"));
+}
+
+#[test]
+fn generated_specs_round_trip_through_json() {
+	let descriptions: Vec<NetworkDescription> = vec![
+		toml::from_str(&description_toml()).unwrap(),
+		serde_json::from_str(&description_json()).unwrap(),
+	];
+	for description in descriptions {
+		let chain_spec = description.into_chain_spec().unwrap();
+		let json = sc_service::chain_ops::build_spec(&chain_spec, false).unwrap();
+
+		let loaded = ChainSpec::from_json_bytes(json.clone().into_bytes()).unwrap();
+		assert_eq!((loaded.name(), loaded.id()), ("Test", "test"));
+		assert_eq!(sc_service::chain_ops::build_spec(&loaded, false).unwrap(), json);
+	}
+}
+
+#[test]
+fn rejects_mixed_authority_forms() {
+	let description =
+		description_toml().replace("seed = \"Alice\"\n", "seed = \"Alice\"\nstash = \"//Alice\"\n");
+	assert!(toml::from_str::<NetworkDescription>(&description).is_err());
+}
+
+#[test]
+fn rejects_ambiguous_seeds() {
+	let seed = AuthorityDescription::Seed { seed: "//Alice".into() };
+	assert!(seed.into_keys().unwrap_err().contains("without the leading `//`"));
+
+	let description = description_toml().replace("sudo = \"//Alice\"", "sudo = \"Alice\"");
+	let description: NetworkDescription = toml::from_str(&description).unwrap();
+	assert!(description.into_chain_spec().unwrap_err().contains("leading `//`"));
+}
+
+#[test]
+fn rejects_accounts_endowed_twice() {
+	let description = format!(
+		"{}[[endowed]]\naccount = \"//Alice//stash\"\nbalance = 1000\n",
+		description_toml()
+	);
+	let description: NetworkDescription = toml::from_str(&description).unwrap();
+	assert!(description.into_chain_spec().unwrap_err().contains("endowed more than once"));
+}
+
+#[test]
+fn rejects_balances_below_the_existential_deposit() {
+	let description =
+		format!("{}[[endowed]]\naccount = \"//Charlie\"\nbalance = 1\n", description_toml());
+	let description: NetworkDescription = toml::from_str(&description).unwrap();
+	assert!(description.into_chain_spec().unwrap_err().contains("existential deposit"));
+}
+
+#[test]
+fn rejects_authorities_sharing_a_stash() {
+	let description = description_toml()
+		.replace("seed = \"Alice\"\n", "seed = \"Alice\"\n[[authorities]]\nseed = \"Alice\"\n");
+	let description: NetworkDescription = toml::from_str(&description).unwrap();
+	assert!(description.into_chain_spec().unwrap_err().contains("The stash"));
+}
+
+#[test]
+fn rejects_authorities_sharing_a_controller_or_key() {
+	let (_, alice_controller, _, alice_grandpa, ..) = authority_keys_from_seed("Alice");
+	let (_, bob_controller, _, bob_grandpa, ..) = authority_keys_from_seed("Bob");
+
+	let cases = [
+		(bob_controller.to_ss58check(), alice_controller.to_ss58check(), "The controller"),
+		(bob_grandpa.to_ss58check(), alice_grandpa.to_ss58check(), "The grandpa key"),
+	];
+	for (bob, alice, error) in cases.iter() {
+		let description = description_toml().replace(bob.as_str(), alice);
+		let description: NetworkDescription = toml::from_str(&description).unwrap();
+		assert!(description.into_chain_spec().unwrap_err().contains(error));
+	}
+}
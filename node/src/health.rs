@@ -0,0 +1,236 @@
+//! An HTTP endpoint reporting the node's health to orchestration: `/health` answers as long as
+//! the node runs, and `/ready` only once it is synced, connected and keeping up with finality.
+//! Both respond with the node's [`Status`] as JSON.
+
+use crate::service::FullClient;
+use async_std::{
+	io::{self, BufReader},
+	net::{TcpListener, TcpStream},
+	prelude::*,
+};
+use futures::StreamExt as _;
+use node_template_runtime::{opaque::Block, BlockNumber, Hash};
+use sc_network::NetworkService;
+use serde::Serialize;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::crypto::Public as _;
+use sp_keystore::{SyncCryptoStore, SyncCryptoStorePtr};
+use sp_runtime::generic::BlockId;
+use std::{
+	net::{Ipv4Addr, SocketAddr},
+	sync::Arc,
+	time::Duration,
+};
+use structopt::StructOpt;
+
+#[cfg(test)]
+mod tests;
+
+/// The most requests answered at once. Further connections wait until one of them is done.
+const MAX_CONCURRENT_REQUESTS: usize = 16;
+/// How long a client has to send its request and read the response.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Parameters of the health endpoint.
+#[derive(Debug, Clone, StructOpt)]
+pub struct HealthParams {
+	/// Serve `/health` and `/ready` over HTTP on this port.
+	#[structopt(long, value_name = "PORT")]
+	pub health_port: Option<u16>,
+
+	/// Listen on all interfaces rather than only on localhost.
+	#[structopt(long)]
+	pub health_external: bool,
+
+	/// The fewest peers a ready node is connected to.
+	#[structopt(long, default_value = "1")]
+	pub ready_min_peers: usize,
+
+	/// The most blocks the finalized block of a ready node is behind its best block.
+	#[structopt(long, default_value = "16")]
+	pub ready_max_finality_lag: BlockNumber,
+
+	/// Only report an authority as ready if it holds the key of a current block author.
+	#[structopt(long)]
+	pub ready_require_authoring: bool,
+}
+
+impl HealthParams {
+	/// The address to listen on, if the endpoint is enabled.
+	pub fn listen_addr(&self) -> Option<SocketAddr> {
+		let ip = if self.health_external { Ipv4Addr::UNSPECIFIED } else { Ipv4Addr::LOCALHOST };
+		self.health_port.map(|port| SocketAddr::new(ip.into(), port))
+	}
+
+	/// Why a node with the given status is not ready, if it is not.
+	pub fn unready_reasons(&self, status: &Status, is_authority: bool) -> Vec<String> {
+		let mut reasons = Vec::new();
+		if status.is_syncing {
+			reasons.push("The node is syncing".into());
+		}
+		if status.peers < self.ready_min_peers {
+			reasons.push(format!(
+				"{} peers connected, {} required",
+				status.peers, self.ready_min_peers
+			));
+		}
+		if status.finality_lag > self.ready_max_finality_lag {
+			reasons.push(format!(
+				"Finality is {} blocks behind, {} allowed",
+				status.finality_lag, self.ready_max_finality_lag
+			));
+		}
+		if is_authority && self.ready_require_authoring && !status.authoring {
+			reasons.push("The node holds no current block author key".into());
+		}
+		reasons
+	}
+}
+
+/// The health of a node.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Status {
+	/// Whether the node is catching up with the chain.
+	pub is_syncing: bool,
+	/// The number of connected peers.
+	pub peers: usize,
+	/// The best block number.
+	pub best_number: BlockNumber,
+	/// The finalized block number.
+	pub finalized_number: BlockNumber,
+	/// How many blocks the finalized block is behind the best one.
+	pub finality_lag: BlockNumber,
+	/// Whether the node authors blocks: it is an authority holding the key of one of the current
+	/// Aura (or BABE) authorities.
+	pub authoring: bool,
+	/// Whether the node is ready.
+	pub ready: bool,
+	/// Why the node is not ready.
+	pub reasons: Vec<String>,
+}
+
+/// Serves the health endpoint of a full node.
+pub struct Health {
+	params: HealthParams,
+	client: Arc<FullClient>,
+	network: Arc<NetworkService<Block, Hash>>,
+	keystore: SyncCryptoStorePtr,
+	is_authority: bool,
+}
+
+impl Health {
+	/// Report on the node `client`, `network` and `keystore` belong to.
+	pub fn new(
+		params: HealthParams,
+		client: Arc<FullClient>,
+		network: Arc<NetworkService<Block, Hash>>,
+		keystore: SyncCryptoStorePtr,
+		is_authority: bool,
+	) -> Self {
+		Self { params, client, network, keystore, is_authority }
+	}
+
+	/// The current status of the node.
+	pub fn status(&self) -> Status {
+		let info = self.client.info();
+		let mut status = Status {
+			is_syncing: self.network.is_major_syncing(),
+			peers: self.network.num_connected(),
+			best_number: info.best_number,
+			finalized_number: info.finalized_number,
+			finality_lag: info.best_number.saturating_sub(info.finalized_number),
+			authoring: self.is_authority && self.holds_author_key(info.best_hash),
+			ready: false,
+			reasons: Vec::new(),
+		};
+		status.reasons = self.params.unready_reasons(&status, self.is_authority);
+		status.ready = status.reasons.is_empty();
+		status
+	}
+
+	/// Whether the keystore holds the key of one of the block authors at block `at`.
+	fn holds_author_key(&self, at: Hash) -> bool {
+		#[cfg(not(feature = "babe"))]
+		let (authorities, key_type) = {
+			use sp_consensus_aura::AuraApi;
+			let authorities = self.client.runtime_api().authorities(&BlockId::Hash(at));
+			(authorities, sp_core::crypto::key_types::AURA)
+		};
+
+		#[cfg(feature = "babe")]
+		let (authorities, key_type) = {
+			use sp_consensus_babe::BabeApi;
+			let epoch = self.client.runtime_api().current_epoch(&BlockId::Hash(at));
+			let authorities = epoch
+				.map(|epoch| epoch.authorities.into_iter().map(|(id, _)| id).collect::<Vec<_>>());
+			(authorities, sp_core::crypto::key_types::BABE)
+		};
+
+		match authorities {
+			Ok(authorities) => authorities.iter().any(|id| {
+				SyncCryptoStore::has_keys(&*self.keystore, &[(id.to_raw_vec(), key_type)])
+			}),
+			Err(e) => {
+				log::warn!("Failed to read the block authors: {}", e);
+				false
+			},
+		}
+	}
+
+	/// Answer requests on `listener` until the node stops.
+	///
+	/// Requests are answered concurrently, so that a client that is slow to send its request does
+	/// not hold up the probes of others.
+	pub async fn serve(self, listener: TcpListener) {
+		let this = &self;
+		listener
+			.incoming()
+			.for_each_concurrent(MAX_CONCURRENT_REQUESTS, |stream| async move {
+				let result = match stream {
+					Ok(stream) => io::timeout(REQUEST_TIMEOUT, this.respond(stream)).await,
+					Err(e) => Err(e),
+				};
+				if let Err(e) = result {
+					log::debug!("Failed to serve a health request: {}", e);
+				}
+			})
+			.await
+	}
+
+	async fn respond(&self, stream: TcpStream) -> io::Result<()> {
+		let mut reader = BufReader::new(&stream);
+		let mut request_line = String::new();
+		reader.read_line(&mut request_line).await?;
+		// Requests have no body; read the headers up to the blank line ending them.
+		let mut header = String::new();
+		while reader.read_line(&mut header).await? > 2 {
+			header.clear();
+		}
+
+		let path = request_line.split_whitespace().nth(1).unwrap_or_default();
+		let status = self.status();
+		let code = match path {
+			"/health" => "200 OK",
+			"/ready" if status.ready => "200 OK",
+			"/ready" => "503 Service Unavailable",
+			_ => "404 Not Found",
+		};
+		let body = serde_json::to_vec(&status).expect("statuses always serialize; qed");
+
+		let mut stream = &stream;
+		stream
+			.write_all(
+				format!(
+					"HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+					 Connection: close\r\n\r\n",
+					code,
+					body.len(),
+				)
+				.as_bytes(),
+			)
+			.await?;
+		stream.write_all(&body).await
+	}
+}
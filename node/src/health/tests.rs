@@ -0,0 +1,61 @@
+use super::{HealthParams, Status};
+use crate::test_utils::parse_args;
+
+fn status(peers: usize, best_number: u32, finalized_number: u32) -> Status {
+	Status {
+		is_syncing: false,
+		peers,
+		best_number,
+		finalized_number,
+		finality_lag: best_number - finalized_number,
+		authoring: false,
+		ready: false,
+		reasons: Vec::new(),
+	}
+}
+
+#[test]
+fn defaults_require_a_peer_and_finality_within_16_blocks() {
+	let params = parse_args::<HealthParams>(&[]);
+	assert_eq!(params.listen_addr(), None);
+
+	assert!(params.unready_reasons(&status(1, 100, 84), false).is_empty());
+	assert_eq!(
+		params.unready_reasons(&status(0, 100, 83), false),
+		vec!["0 peers connected, 1 required", "Finality is 17 blocks behind, 16 allowed"]
+	);
+
+	let syncing = Status { is_syncing: true, ..status(5, 100, 100) };
+	assert_eq!(params.unready_reasons(&syncing, false), vec!["The node is syncing"]);
+}
+
+#[test]
+fn thresholds_are_configurable() {
+	let params = parse_args::<HealthParams>(&[
+		"--health-port",
+		"9955",
+		"--ready-min-peers",
+		"0",
+		"--ready-max-finality-lag",
+		"2",
+		"--ready-require-authoring",
+	]);
+	assert_eq!(params.listen_addr(), Some("127.0.0.1:9955".parse().unwrap()));
+
+	assert!(params.unready_reasons(&status(0, 10, 8), false).is_empty());
+	assert_eq!(params.unready_reasons(&status(0, 10, 7), false).len(), 1);
+
+	// Only authorities are required to author.
+	assert_eq!(
+		params.unready_reasons(&status(0, 10, 8), true),
+		vec!["The node holds no current block author key"]
+	);
+	let authoring = Status { authoring: true, ..status(0, 10, 8) };
+	assert!(params.unready_reasons(&authoring, true).is_empty());
+}
+
+#[test]
+fn listens_externally_on_request() {
+	let params = parse_args::<HealthParams>(&["--health-port", "9955", "--health-external"]);
+	assert_eq!(params.listen_addr(), Some("0.0.0.0:9955".parse().unwrap()));
+}
@@ -0,0 +1,637 @@
+//! An embedded indexer: follows finalized blocks and stores their extrinsics, events and balance
+//! transfers in a SQLite database, so that questions such as "which transfers involve this
+//! account" are answered by the node itself rather than by an external indexer.
+
+use crate::{service::FullClient, storage_keys::events_key};
+use codec::{Decode, Encode};
+use frame_support::traits::GetCallMetadata;
+use frame_system::{EventRecord, Phase};
+use futures::prelude::*;
+use node_template_runtime::{
+	AccountId, Balance, BlockNumber, Call, Event, Hash, TimestampCall, UncheckedExtrinsic,
+};
+use rusqlite::{params, types::Type, Connection, OptionalExtension, Row};
+use sc_client_api::{BlockBackend, BlockchainEvents, StorageProvider};
+use sc_service::{Configuration, KeepBlocks};
+use serde::{Deserialize, Serialize, Serializer};
+use sp_blockchain::HeaderBackend;
+use sp_core::blake2_256;
+use sp_rpc::number::NumberOrHex;
+use sp_runtime::{generic::BlockId, MultiAddress};
+use std::{
+	convert::TryFrom,
+	path::{Path, PathBuf},
+	sync::{Arc, Mutex, MutexGuard},
+	time::Duration,
+};
+use structopt::StructOpt;
+
+#[cfg(test)]
+mod tests;
+
+/// How many results a query returns if the page does not say.
+pub const DEFAULT_LIMIT: u32 = 100;
+/// The most results a query returns.
+pub const MAX_LIMIT: u32 = 1000;
+
+/// How long indexing waits before its first retry after failing. Each failure in a row doubles it.
+const MIN_RETRY_DELAY: Duration = Duration::from_secs(1);
+/// The longest indexing waits between retries.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+const SCHEMA: &str = "
+	CREATE TABLE IF NOT EXISTS blocks (
+		number INTEGER PRIMARY KEY,
+		hash BLOB NOT NULL,
+		timestamp INTEGER
+	);
+	CREATE TABLE IF NOT EXISTS extrinsics (
+		block_number INTEGER NOT NULL REFERENCES blocks (number),
+		extrinsic_index INTEGER NOT NULL,
+		hash BLOB NOT NULL,
+		signer BLOB,
+		pallet TEXT NOT NULL,
+		call TEXT NOT NULL,
+		success INTEGER NOT NULL,
+		PRIMARY KEY (block_number, extrinsic_index)
+	);
+	CREATE INDEX IF NOT EXISTS extrinsics_by_signer ON extrinsics (signer, block_number);
+	CREATE INDEX IF NOT EXISTS extrinsics_by_call ON extrinsics (pallet, call, block_number);
+	CREATE TABLE IF NOT EXISTS events (
+		block_number INTEGER NOT NULL REFERENCES blocks (number),
+		event_index INTEGER NOT NULL,
+		extrinsic_index INTEGER,
+		pallet TEXT NOT NULL,
+		name TEXT NOT NULL,
+		details TEXT NOT NULL,
+		PRIMARY KEY (block_number, event_index)
+	);
+	CREATE INDEX IF NOT EXISTS events_by_name ON events (pallet, name, block_number);
+	CREATE TABLE IF NOT EXISTS transfers (
+		block_number INTEGER NOT NULL REFERENCES blocks (number),
+		event_index INTEGER NOT NULL,
+		extrinsic_index INTEGER,
+		sender BLOB NOT NULL,
+		recipient BLOB NOT NULL,
+		amount TEXT NOT NULL,
+		PRIMARY KEY (block_number, event_index)
+	);
+	CREATE INDEX IF NOT EXISTS transfers_by_sender ON transfers (sender, block_number);
+	CREATE INDEX IF NOT EXISTS transfers_by_recipient ON transfers (recipient, block_number);
+";
+
+/// Parameters of the embedded indexer.
+#[derive(Debug, Clone, StructOpt)]
+pub struct IndexerParams {
+	/// Index finalized blocks into an embedded database, queried over the `indexer_*` RPCs.
+	/// Requires `--pruning archive`.
+	#[structopt(long)]
+	pub indexer: bool,
+
+	/// The indexer database. Defaults to `indexer.sqlite` in the chain's directory.
+	#[structopt(long, parse(from_os_str))]
+	pub indexer_path: Option<PathBuf>,
+}
+
+impl IndexerParams {
+	/// Open the indexer database, if the indexer is enabled.
+	///
+	/// The indexer reads every finalized block's body and events, so it requires a node that
+	/// keeps all of them: one started with `--pruning archive` and without `--keep-blocks`.
+	pub fn open(&self, config: &Configuration) -> Result<Option<Arc<Indexer>>, String> {
+		if !self.indexer {
+			return Ok(None)
+		}
+		if !config.state_pruning.is_archive() || !matches!(config.keep_blocks, KeepBlocks::All) {
+			return Err("The indexer requires `--pruning archive` and keeping all blocks: it reads \
+				the events of every finalized block from its state"
+				.into())
+		}
+
+		let path = match (&self.indexer_path, &config.base_path) {
+			(Some(path), _) => path.clone(),
+			(None, Some(base_path)) =>
+				base_path.config_dir(config.chain_spec.id()).join("indexer.sqlite"),
+			(None, None) => return Err("`--indexer-path` is required without a base path".into()),
+		};
+		Indexer::open(&path)
+			.map(|indexer| Some(Arc::new(indexer)))
+			.map_err(|e| format!("Failed to open the indexer database {}: {}", path.display(), e))
+	}
+}
+
+/// An extrinsic of a finalized block.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexedExtrinsic {
+	/// The block it is included in.
+	pub block_number: BlockNumber,
+	/// Its index in the block.
+	pub index: u32,
+	/// The BLAKE2 hash of its encoding, as the transaction pool identifies it.
+	pub hash: Hash,
+	/// The signer, unless the extrinsic is unsigned or its signer is given by account index.
+	pub signer: Option<AccountId>,
+	/// The name of the called pallet, such as `Balances`.
+	pub pallet: String,
+	/// The name of the call, such as `transfer`.
+	pub call: String,
+	/// Whether the call was dispatched successfully.
+	pub success: bool,
+}
+
+/// An event of a finalized block.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexedEvent {
+	/// The block that emitted it.
+	pub block_number: BlockNumber,
+	/// Its index among the events of the block.
+	pub index: u32,
+	/// The extrinsic that emitted the event, if any.
+	pub extrinsic_index: Option<u32>,
+	/// The name of the emitting pallet, such as `Balances`.
+	pub pallet: String,
+	/// The name of the event, such as `Transfer`.
+	pub name: String,
+	/// The event and its fields, in Rust's debug format.
+	pub details: String,
+}
+
+/// A balance transfer of a finalized block.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Transfer {
+	/// The block it happened in.
+	pub block_number: BlockNumber,
+	/// The index of the `Balances::Transfer` event.
+	pub event_index: u32,
+	/// The extrinsic that made the transfer.
+	pub extrinsic_index: Option<u32>,
+	/// The account debited.
+	pub from: AccountId,
+	/// The account credited.
+	pub to: AccountId,
+	/// The amount transferred.
+	#[serde(serialize_with = "serialize_balance")]
+	pub amount: Balance,
+}
+
+/// What the indexer stores of a block.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IndexedBlock {
+	/// The block number.
+	pub number: BlockNumber,
+	/// The block hash.
+	pub hash: Hash,
+	/// The time set by the timestamp inherent, in milliseconds.
+	pub timestamp: Option<u64>,
+	/// The extrinsics of the block.
+	pub extrinsics: Vec<IndexedExtrinsic>,
+	/// The events the block emitted.
+	pub events: Vec<IndexedEvent>,
+	/// The balance transfers among the events.
+	pub transfers: Vec<Transfer>,
+}
+
+/// Selects extrinsics. Unset fields match any extrinsic.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ExtrinsicFilter {
+	/// The signing account.
+	pub signer: Option<AccountId>,
+	/// A pallet name, such as `Balances`.
+	pub pallet: Option<String>,
+	/// A call name, such as `transfer`.
+	pub call: Option<String>,
+}
+
+/// Selects events. Unset fields match any event.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct EventFilter {
+	/// A pallet name, such as `Balances`.
+	pub pallet: Option<String>,
+	/// An event name, such as `Transfer`.
+	pub name: Option<String>,
+}
+
+/// The blocks a query searches, from `to_block` down to `from_block`, and how many results it
+/// returns at most. Results are ordered newest first.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Page {
+	/// The oldest block searched, the genesis block by default.
+	pub from_block: Option<BlockNumber>,
+	/// The newest block searched, the last indexed one by default.
+	pub to_block: Option<BlockNumber>,
+	/// Defaults to [`DEFAULT_LIMIT`], and is capped at [`MAX_LIMIT`].
+	pub limit: Option<u32>,
+}
+
+impl Page {
+	fn bounds(&self) -> (BlockNumber, BlockNumber, u32) {
+		(
+			self.from_block.unwrap_or(0),
+			self.to_block.unwrap_or(BlockNumber::MAX),
+			self.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT),
+		)
+	}
+}
+
+/// The indexer database.
+pub struct Indexer {
+	connection: Mutex<Connection>,
+}
+
+impl Indexer {
+	/// Open the database at `path`, creating it if needed.
+	pub fn open(path: &Path) -> rusqlite::Result<Self> {
+		Self::new(Connection::open(path)?)
+	}
+
+	/// Open a database that only lives in memory.
+	pub fn in_memory() -> rusqlite::Result<Self> {
+		Self::new(Connection::open_in_memory()?)
+	}
+
+	fn new(connection: Connection) -> rusqlite::Result<Self> {
+		connection.execute_batch(SCHEMA)?;
+		Ok(Self { connection: Mutex::new(connection) })
+	}
+
+	fn connection(&self) -> MutexGuard<Connection> {
+		self.connection.lock().expect("never poisoned; qed")
+	}
+
+	/// The number and hash of the last indexed block.
+	pub fn last_indexed(&self) -> rusqlite::Result<Option<(BlockNumber, Hash)>> {
+		self.connection()
+			.query_row(
+				"SELECT number, hash FROM blocks ORDER BY number DESC LIMIT 1",
+				params![],
+				|row| Ok((row.get(0)?, bytes32_at(row, 1)?.into())),
+			)
+			.optional()
+	}
+
+	/// Store `block`, which must follow the last indexed block.
+	pub fn insert(&self, block: &IndexedBlock) -> rusqlite::Result<()> {
+		let mut connection = self.connection();
+		let transaction = connection.transaction()?;
+
+		transaction.execute(
+			"INSERT INTO blocks (number, hash, timestamp) VALUES (?1, ?2, ?3)",
+			params![block.number, block.hash.as_bytes(), block.timestamp.map(|t| t as i64)],
+		)?;
+		for extrinsic in &block.extrinsics {
+			transaction.execute(
+				"INSERT INTO extrinsics
+					(block_number, extrinsic_index, hash, signer, pallet, call, success)
+					VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+				params![
+					extrinsic.block_number,
+					extrinsic.index,
+					extrinsic.hash.as_bytes(),
+					extrinsic.signer.as_ref().map(AsRef::<[u8]>::as_ref),
+					extrinsic.pallet,
+					extrinsic.call,
+					extrinsic.success,
+				],
+			)?;
+		}
+		for event in &block.events {
+			transaction.execute(
+				"INSERT INTO events
+					(block_number, event_index, extrinsic_index, pallet, name, details)
+					VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+				params![
+					event.block_number,
+					event.index,
+					event.extrinsic_index,
+					event.pallet,
+					event.name,
+					event.details,
+				],
+			)?;
+		}
+		for transfer in &block.transfers {
+			transaction.execute(
+				"INSERT INTO transfers
+					(block_number, event_index, extrinsic_index, sender, recipient, amount)
+					VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+				params![
+					transfer.block_number,
+					transfer.event_index,
+					transfer.extrinsic_index,
+					AsRef::<[u8]>::as_ref(&transfer.from),
+					AsRef::<[u8]>::as_ref(&transfer.to),
+					transfer.amount.to_string(),
+				],
+			)?;
+		}
+
+		transaction.commit()
+	}
+
+	/// The transfers from or to `account`.
+	pub fn transfers(&self, account: &AccountId, page: &Page) -> rusqlite::Result<Vec<Transfer>> {
+		let (from_block, to_block, limit) = page.bounds();
+		let connection = self.connection();
+		let mut statement = connection.prepare_cached(
+			"SELECT block_number, event_index, extrinsic_index, sender, recipient, amount
+				FROM transfers
+				WHERE (sender = ?1 OR recipient = ?1) AND block_number BETWEEN ?2 AND ?3
+				ORDER BY block_number DESC, event_index DESC
+				LIMIT ?4",
+		)?;
+		let transfers = statement
+			.query_map(
+				params![AsRef::<[u8]>::as_ref(account), from_block, to_block, limit],
+				|row| {
+					Ok(Transfer {
+						block_number: row.get(0)?,
+						event_index: row.get(1)?,
+						extrinsic_index: row.get(2)?,
+						from: AccountId::new(bytes32_at(row, 3)?),
+						to: AccountId::new(bytes32_at(row, 4)?),
+						amount: balance_at(row, 5)?,
+					})
+				},
+			)?
+			.collect::<rusqlite::Result<_>>()?;
+		Ok(transfers)
+	}
+
+	/// The extrinsics `filter` selects.
+	pub fn extrinsics(
+		&self,
+		filter: &ExtrinsicFilter,
+		page: &Page,
+	) -> rusqlite::Result<Vec<IndexedExtrinsic>> {
+		let (from_block, to_block, limit) = page.bounds();
+		let connection = self.connection();
+		let mut statement = connection.prepare_cached(
+			"SELECT block_number, extrinsic_index, hash, signer, pallet, call, success
+				FROM extrinsics
+				WHERE (?1 IS NULL OR signer = ?1)
+					AND (?2 IS NULL OR pallet = ?2)
+					AND (?3 IS NULL OR call = ?3)
+					AND block_number BETWEEN ?4 AND ?5
+				ORDER BY block_number DESC, extrinsic_index DESC
+				LIMIT ?6",
+		)?;
+		let extrinsics = statement
+			.query_map(
+				params![
+					filter.signer.as_ref().map(AsRef::<[u8]>::as_ref),
+					filter.pallet,
+					filter.call,
+					from_block,
+					to_block,
+					limit,
+				],
+				|row| {
+					let signer = row
+						.get::<_, Option<Vec<u8>>>(3)?
+						.map(|bytes| to_bytes32(3, bytes).map(AccountId::new))
+						.transpose()?;
+					Ok(IndexedExtrinsic {
+						block_number: row.get(0)?,
+						index: row.get(1)?,
+						hash: bytes32_at(row, 2)?.into(),
+						signer,
+						pallet: row.get(4)?,
+						call: row.get(5)?,
+						success: row.get(6)?,
+					})
+				},
+			)?
+			.collect::<rusqlite::Result<_>>()?;
+		Ok(extrinsics)
+	}
+
+	/// The events `filter` selects.
+	pub fn events(&self, filter: &EventFilter, page: &Page) -> rusqlite::Result<Vec<IndexedEvent>> {
+		let (from_block, to_block, limit) = page.bounds();
+		let connection = self.connection();
+		let mut statement = connection.prepare_cached(
+			"SELECT block_number, event_index, extrinsic_index, pallet, name, details
+				FROM events
+				WHERE (?1 IS NULL OR pallet = ?1)
+					AND (?2 IS NULL OR name = ?2)
+					AND block_number BETWEEN ?3 AND ?4
+				ORDER BY block_number DESC, event_index DESC
+				LIMIT ?5",
+		)?;
+		let events = statement
+			.query_map(params![filter.pallet, filter.name, from_block, to_block, limit], |row| {
+				Ok(IndexedEvent {
+					block_number: row.get(0)?,
+					index: row.get(1)?,
+					extrinsic_index: row.get(2)?,
+					pallet: row.get(3)?,
+					name: row.get(4)?,
+					details: row.get(5)?,
+				})
+			})?
+			.collect::<rusqlite::Result<_>>()?;
+		Ok(events)
+	}
+
+	/// Index the finalized blocks that are not indexed yet.
+	fn catch_up(&self, client: &FullClient) -> Result<(), String> {
+		let next = match self.last_indexed().map_err(|e| e.to_string())? {
+			Some((number, hash)) => {
+				if client.hash(number).map_err(|e| e.to_string())? != Some(hash) {
+					return Err(format!(
+						"Block #{} is not the one indexed: the database belongs to another chain",
+						number
+					))
+				}
+				number + 1
+			},
+			None => 0,
+		};
+
+		for number in next..=client.info().finalized_number {
+			let hash = client
+				.hash(number)
+				.map_err(|e| e.to_string())?
+				.ok_or_else(|| format!("Finalized block #{} not found", number))?;
+			let block = fetch_block(client, number, hash)?;
+			self.insert(&block).map_err(|e| e.to_string())?;
+		}
+		Ok(())
+	}
+}
+
+impl IndexedBlock {
+	/// Decode what the indexer stores from a block's extrinsics and the events it emitted.
+	pub fn new(
+		number: BlockNumber,
+		hash: Hash,
+		extrinsics: &[UncheckedExtrinsic],
+		records: &[EventRecord<Event, Hash>],
+	) -> Self {
+		let timestamp = extrinsics.iter().find_map(|extrinsic| match &extrinsic.function {
+			Call::Timestamp(TimestampCall::set(now)) => Some(*now),
+			_ => None,
+		});
+
+		let failed = |index: u32| {
+			records.iter().any(|record| {
+				record.phase == Phase::ApplyExtrinsic(index) &&
+					matches!(
+						record.event,
+						Event::System(frame_system::Event::ExtrinsicFailed(..))
+					)
+			})
+		};
+		let extrinsics = extrinsics
+			.iter()
+			.enumerate()
+			.map(|(index, extrinsic)| {
+				let index = index as u32;
+				let metadata = extrinsic.function.get_call_metadata();
+				IndexedExtrinsic {
+					block_number: number,
+					index,
+					hash: blake2_256(&extrinsic.encode()).into(),
+					signer: extrinsic.signature.as_ref().and_then(|(address, ..)| match address {
+						MultiAddress::Id(id) => Some(id.clone()),
+						_ => None,
+					}),
+					pallet: metadata.pallet_name.into(),
+					call: metadata.function_name.into(),
+					success: !failed(index),
+				}
+			})
+			.collect();
+
+		let mut events = Vec::with_capacity(records.len());
+		let mut transfers = Vec::new();
+		for (index, record) in records.iter().enumerate() {
+			let index = index as u32;
+			let extrinsic_index = match record.phase {
+				Phase::ApplyExtrinsic(extrinsic_index) => Some(extrinsic_index),
+				_ => None,
+			};
+
+			if let Event::Balances(pallet_balances::Event::Transfer(from, to, amount)) =
+				&record.event
+			{
+				transfers.push(Transfer {
+					block_number: number,
+					event_index: index,
+					extrinsic_index,
+					from: from.clone(),
+					to: to.clone(),
+					amount: *amount,
+				});
+			}
+
+			let details = format!("{:?}", record.event);
+			let (pallet, name) = event_names(&details);
+			events.push(IndexedEvent {
+				block_number: number,
+				index,
+				extrinsic_index,
+				pallet,
+				name,
+				details,
+			});
+		}
+
+		Self { number, hash, timestamp, extrinsics, events, transfers }
+	}
+}
+
+/// Index finalized blocks as they are finalized, until the node stops.
+///
+/// Failures, such as a busy database, are retried with an exponential backoff rather than stopping
+/// indexing, so that it resumes once they clear.
+pub async fn follow_finalized(indexer: Arc<Indexer>, client: Arc<FullClient>) {
+	let mut finality_notifications = client.finality_notification_stream();
+	let mut retry_delay = MIN_RETRY_DELAY;
+	loop {
+		match indexer.catch_up(&client) {
+			Ok(()) => {
+				retry_delay = MIN_RETRY_DELAY;
+				if finality_notifications.next().await.is_none() {
+					return
+				}
+			},
+			Err(e) => {
+				log::error!(
+					target: "indexer",
+					"Indexing failed, retrying in {}s: {}",
+					retry_delay.as_secs(),
+					e,
+				);
+				futures_timer::Delay::new(retry_delay).await;
+				retry_delay = (retry_delay * 2).min(MAX_RETRY_DELAY);
+			},
+		}
+	}
+}
+
+fn fetch_block(
+	client: &FullClient,
+	number: BlockNumber,
+	hash: Hash,
+) -> Result<IndexedBlock, String> {
+	let at = BlockId::Hash(hash);
+
+	let extrinsics = client
+		.block_body(&at)
+		.map_err(|e| e.to_string())?
+		.ok_or_else(|| format!("The body of block #{} is not available", number))?
+		.iter()
+		.map(|extrinsic| UncheckedExtrinsic::decode(&mut &extrinsic.encode()[..]))
+		.collect::<Result<Vec<_>, _>>()
+		.map_err(|e| format!("Failed to decode an extrinsic of block #{}: {}", number, e))?;
+
+	// Without an archive node, the state, and with it the events, of old blocks is pruned.
+	let events = client
+		.storage(&at, &events_key())
+		.map_err(|e| format!("The events of block #{} are not available: {}", number, e))?;
+	let records = match events {
+		Some(events) => Vec::<EventRecord<Event, Hash>>::decode(&mut &events.0[..])
+			.map_err(|e| format!("Failed to decode the events of block #{}: {}", number, e))?,
+		None => Vec::new(),
+	};
+
+	Ok(IndexedBlock::new(number, hash, &extrinsics, &records))
+}
+
+/// The pallet and event names at the start of an event's debug output, such as `Balances` and
+/// `Transfer` in `Balances(Transfer(..))`. The native runtime carries no event names otherwise.
+fn event_names(details: &str) -> (String, String) {
+	let mut names = details.split(|c: char| !c.is_alphanumeric() && c != '_');
+	let pallet = names.next().unwrap_or_default().into();
+	let name = names.next().unwrap_or_default().into();
+	(pallet, name)
+}
+
+/// Hashes and accounts are stored as 32-byte blobs.
+fn bytes32_at(row: &Row, index: usize) -> rusqlite::Result<[u8; 32]> {
+	let bytes: Vec<u8> = row.get(index)?;
+	to_bytes32(index, bytes)
+}
+
+fn to_bytes32(index: usize, bytes: Vec<u8>) -> rusqlite::Result<[u8; 32]> {
+	<[u8; 32]>::try_from(&bytes[..])
+		.map_err(|e| rusqlite::Error::FromSqlConversionFailure(index, Type::Blob, Box::new(e)))
+}
+
+/// Balances do not fit SQLite's integers, so they are stored as decimal strings.
+fn balance_at(row: &Row, index: usize) -> rusqlite::Result<Balance> {
+	row.get::<_, String>(index)?
+		.parse()
+		.map_err(|e| rusqlite::Error::FromSqlConversionFailure(index, Type::Text, Box::new(e)))
+}
+
+fn serialize_balance<S: Serializer>(balance: &Balance, serializer: S) -> Result<S::Ok, S::Error> {
+	NumberOrHex::from(*balance).serialize(serializer)
+}
@@ -0,0 +1,150 @@
+use super::{EventFilter, ExtrinsicFilter, IndexedBlock, Indexer, Page, Transfer};
+use frame_support::weights::DispatchInfo;
+use frame_system::{EventRecord, Phase};
+use node_template_runtime::{
+	AccountId, Balance, BalancesCall, BlockNumber, Call, Event, Hash, TimestampCall,
+	UncheckedExtrinsic,
+};
+use sp_runtime::{DispatchError, MultiAddress};
+
+fn account(seed: u8) -> AccountId {
+	AccountId::new([seed; 32])
+}
+
+fn record(extrinsic_index: u32, event: Event) -> EventRecord<Event, Hash> {
+	EventRecord { phase: Phase::ApplyExtrinsic(extrinsic_index), event, topics: vec![] }
+}
+
+/// A block with a timestamp inherent and a transfer of `amount` from `from` to `to`, which failed
+/// if `amount` is 0.
+fn block(number: BlockNumber, from: u8, to: u8, amount: Balance) -> IndexedBlock {
+	let extrinsics = vec![
+		UncheckedExtrinsic::new_unsigned(Call::Timestamp(TimestampCall::set(number as u64 * 6000))),
+		UncheckedExtrinsic::new_unsigned(Call::Balances(BalancesCall::transfer(
+			MultiAddress::Id(account(to)),
+			amount,
+		))),
+	];
+
+	let mut records =
+		vec![record(0, Event::System(frame_system::Event::ExtrinsicSuccess(Default::default())))];
+	if amount == 0 {
+		records.push(record(
+			1,
+			Event::System(frame_system::Event::ExtrinsicFailed(
+				DispatchError::BadOrigin,
+				DispatchInfo::default(),
+			)),
+		));
+	} else {
+		records.push(record(
+			1,
+			Event::Balances(pallet_balances::Event::Transfer(account(from), account(to), amount)),
+		));
+		records.push(record(
+			1,
+			Event::System(frame_system::Event::ExtrinsicSuccess(Default::default())),
+		));
+	}
+
+	IndexedBlock::new(number, Hash::repeat_byte(number as u8), &extrinsics, &records)
+}
+
+#[test]
+fn decodes_extrinsics_and_events() {
+	let indexed = block(1, 1, 2, 10);
+
+	assert_eq!(indexed.timestamp, Some(6000));
+	assert_eq!(indexed.extrinsics.len(), 2);
+	assert_eq!(
+		(indexed.extrinsics[1].pallet.as_str(), indexed.extrinsics[1].call.as_str()),
+		("Balances", "transfer")
+	);
+	assert_eq!(indexed.extrinsics[1].signer, None);
+	assert!(indexed.extrinsics.iter().all(|extrinsic| extrinsic.success));
+
+	let names: Vec<_> = indexed
+		.events
+		.iter()
+		.map(|event| (event.pallet.as_str(), event.name.as_str()))
+		.collect();
+	assert_eq!(
+		names,
+		vec![
+			("System", "ExtrinsicSuccess"),
+			("Balances", "Transfer"),
+			("System", "ExtrinsicSuccess")
+		]
+	);
+	assert_eq!(
+		indexed.transfers,
+		vec![Transfer {
+			block_number: 1,
+			event_index: 1,
+			extrinsic_index: Some(1),
+			from: account(1),
+			to: account(2),
+			amount: 10,
+		}]
+	);
+
+	let failed = block(2, 1, 2, 0);
+	assert!(failed.extrinsics[0].success);
+	assert!(!failed.extrinsics[1].success);
+	assert!(failed.transfers.is_empty());
+}
+
+#[test]
+fn queries_transfers_by_account() {
+	let indexer = Indexer::in_memory().unwrap();
+	assert_eq!(indexer.last_indexed().unwrap(), None);
+
+	for (number, from, to) in [(0, 1, 2), (1, 2, 3), (2, 3, 1), (3, 2, 3)].iter().copied() {
+		indexer.insert(&block(number, from, to, 10 + number as Balance)).unwrap();
+	}
+	assert_eq!(indexer.last_indexed().unwrap(), Some((3, Hash::repeat_byte(3))));
+
+	let blocks_of = |transfers: Vec<Transfer>| {
+		transfers.iter().map(|transfer| transfer.block_number).collect::<Vec<_>>()
+	};
+	// Newest first, whether sent or received.
+	assert_eq!(blocks_of(indexer.transfers(&account(1), &Page::default()).unwrap()), vec![2, 0]);
+	assert_eq!(blocks_of(indexer.transfers(&account(2), &Page::default()).unwrap()), vec![3, 1, 0]);
+	assert_eq!(indexer.transfers(&account(2), &Page::default()).unwrap()[0].amount, 13);
+
+	let page = Page { from_block: Some(1), to_block: Some(3), limit: Some(1) };
+	assert_eq!(blocks_of(indexer.transfers(&account(2), &page).unwrap()), vec![3]);
+	let page = Page { from_block: Some(1), to_block: Some(2), limit: None };
+	assert_eq!(blocks_of(indexer.transfers(&account(2), &page).unwrap()), vec![1]);
+	assert!(indexer.transfers(&account(4), &Page::default()).unwrap().is_empty());
+}
+
+#[test]
+fn filters_extrinsics_and_events() {
+	let indexer = Indexer::in_memory().unwrap();
+	indexer.insert(&block(0, 1, 2, 10)).unwrap();
+	indexer.insert(&block(1, 1, 2, 0)).unwrap();
+
+	let transfers = ExtrinsicFilter {
+		pallet: Some("Balances".into()),
+		call: Some("transfer".into()),
+		..Default::default()
+	};
+	let extrinsics = indexer.extrinsics(&transfers, &Page::default()).unwrap();
+	assert_eq!(
+		extrinsics.iter().map(|x| (x.block_number, x.success)).collect::<Vec<_>>(),
+		vec![(1, false), (0, true)]
+	);
+	assert_eq!(extrinsics[1], block(0, 1, 2, 10).extrinsics[1]);
+	assert_eq!(indexer.extrinsics(&Default::default(), &Page::default()).unwrap().len(), 4);
+	let signed = ExtrinsicFilter { signer: Some(account(1)), ..Default::default() };
+	assert!(indexer.extrinsics(&signed, &Page::default()).unwrap().is_empty());
+
+	let failures =
+		EventFilter { pallet: Some("System".into()), name: Some("ExtrinsicFailed".into()) };
+	let events = indexer.events(&failures, &Page::default()).unwrap();
+	assert_eq!(events.len(), 1);
+	assert_eq!((events[0].block_number, events[0].extrinsic_index), (1, Some(1)));
+	let balances = EventFilter { pallet: Some("Balances".into()), name: None };
+	assert_eq!(indexer.events(&balances, &Page::default()).unwrap().len(), 1);
+}
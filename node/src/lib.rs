@@ -1,3 +1,11 @@
+pub mod backoff;
 pub mod chain_spec;
+pub mod health;
+pub mod indexer;
+pub mod remote_keystore;
 pub mod rpc;
+pub mod sealing;
 pub mod service;
+pub mod storage_keys;
+#[cfg(test)]
+mod test_utils;
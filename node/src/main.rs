@@ -1,12 +1,21 @@
 //! Substrate Node Template CLI library.
 #![warn(missing_docs)]
 
+mod backoff;
 mod chain_spec;
 #[macro_use]
 mod service;
 mod cli;
 mod command;
+mod generate_spec;
+mod health;
+mod indexer;
+mod remote_keystore;
 mod rpc;
+mod sealing;
+mod storage_keys;
+#[cfg(test)]
+mod test_utils;
 
 fn main() -> sc_cli::Result<()> {
 	command::run()
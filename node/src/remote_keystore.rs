@@ -0,0 +1,533 @@
+//! A keystore whose keys live on a remote signer, so that validator keys can be kept on a
+//! separate, hardened host.
+//!
+//! [`RemoteKeystore`] forwards every keystore operation to a signer as a JSON [`Request`], over
+//! HTTP (`http://<host>:<port>`) or a Unix socket (`unix:<path>`), and the signer answers with a
+//! JSON [`Response`]. The `remote-signer` subcommand runs a reference [`signer`] serving a local
+//! keystore.
+//!
+//! Over HTTP, requests carry a token shared by the node and the signer, read from
+//! `--keystore-token-file`. The signer's Unix socket is only accessible to its user, who must thus
+//! run the node as well. Neither protects requests in transit: HTTP signers should still only be
+//! reachable over a private network.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use sp_core::{
+	crypto::{CryptoTypeId, CryptoTypePublicPair, KeyTypeId},
+	ecdsa, ed25519, sr25519, Bytes,
+};
+use sp_keystore::{
+	vrf::{VRFSignature, VRFTranscriptData, VRFTranscriptValue},
+	CryptoStore, Error, SyncCryptoStore,
+};
+use std::{
+	convert::TryFrom,
+	fmt, fs,
+	io::{BufRead, BufReader, Read, Write},
+	net::TcpStream,
+	os::unix::net::UnixStream,
+	path::{Path, PathBuf},
+	str::FromStr,
+	time::Duration,
+};
+use structopt::StructOpt;
+
+pub mod signer;
+
+#[cfg(test)]
+mod tests;
+
+/// How long to wait for the signer before giving up on a request.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Parameters of the remote keystore, used along with `--keystore-uri`.
+#[derive(Debug, Clone, StructOpt)]
+pub struct RemoteKeystoreParams {
+	/// A file holding the token of the signer given by `--keystore-uri`. Required for HTTP
+	/// signers.
+	#[structopt(long, parse(from_os_str))]
+	pub keystore_token_file: Option<PathBuf>,
+}
+
+impl RemoteKeystoreParams {
+	/// The token to present to the signer, if any.
+	pub fn token(&self) -> Result<Option<String>, String> {
+		self.keystore_token_file
+			.as_ref()
+			.map(|path| {
+				fs::read_to_string(path)
+					.map(|token| token.trim().to_string())
+					.map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+			})
+			.transpose()
+	}
+}
+
+/// Where a signer listens.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Endpoint {
+	/// `http://<host>:<port>`.
+	Http(String),
+	/// `unix:<path>`.
+	Unix(PathBuf),
+}
+
+impl FromStr for Endpoint {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		if let Some(address) = s.strip_prefix("http://") {
+			Ok(Self::Http(address.trim_end_matches('/').into()))
+		} else if let Some(path) = s.strip_prefix("unix:") {
+			Ok(Self::Unix(path.trim_start_matches("//").into()))
+		} else {
+			Err(format!("expected `http://<host>:<port>` or `unix:<path>`, got `{}`", s))
+		}
+	}
+}
+
+impl fmt::Display for Endpoint {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Self::Http(address) => write!(f, "http://{}", address),
+			Self::Unix(path) => write!(f, "unix:{}", path.display()),
+		}
+	}
+}
+
+/// A keystore operation, as sent to the signer.
+///
+/// Key types and crypto types are sent as their four bytes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "method", content = "params")]
+pub enum Request {
+	/// List the public keys of a key type, of every crypto type.
+	Keys { key_type: [u8; 4] },
+	/// Generate a new key, from `seed` if given.
+	GenerateNew { key_type: [u8; 4], crypto: [u8; 4], seed: Option<String> },
+	/// Insert a key pair given by its secret URI.
+	InsertUnknown { key_type: [u8; 4], suri: String, public: Bytes },
+	/// Whether all the given keys are in the keystore.
+	HasKeys { keys: Vec<(Bytes, [u8; 4])> },
+	/// Sign `message` with the key `public` of the given crypto type.
+	SignWith { key_type: [u8; 4], crypto: [u8; 4], public: Bytes, message: Bytes },
+	/// Generate a VRF signature over a transcript with an sr25519 key.
+	VrfSign {
+		key_type: [u8; 4],
+		public: Bytes,
+		label: Bytes,
+		items: Vec<(String, TranscriptValue)>,
+	},
+	/// Sign a 32-byte message hash with an ECDSA key.
+	EcdsaSignPrehashed { key_type: [u8; 4], public: Bytes, message: Bytes },
+}
+
+/// A value appended to a VRF transcript.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TranscriptValue {
+	/// A byte string.
+	Bytes(Bytes),
+	/// An integer.
+	U64(u64),
+}
+
+/// The signer's answer to a [`Request`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Response {
+	/// The crypto type and bytes of public keys.
+	Keys(Vec<([u8; 4], Bytes)>),
+	/// A generated public key.
+	Public(Bytes),
+	/// The request succeeded with nothing to return.
+	Done,
+	/// Whether all the keys asked about are present.
+	HasKeys(bool),
+	/// A signature, or `None` if the keystore does not have the key.
+	Signature(Option<Bytes>),
+	/// The output and proof of a VRF signature, or `None` if the keystore does not have the key.
+	VrfSignature(Option<(Bytes, Bytes)>),
+}
+
+/// A keystore forwarding every operation to a remote signer.
+#[derive(Clone)]
+pub struct RemoteKeystore {
+	endpoint: Endpoint,
+	token: Option<String>,
+}
+
+impl RemoteKeystore {
+	/// Create a keystore using the signer at `uri`, e.g. `unix:/run/signer.sock`, presenting
+	/// `token` to HTTP signers.
+	pub fn open(uri: &str, token: Option<String>) -> Result<Self, String> {
+		let endpoint = uri.parse()?;
+		if matches!(endpoint, Endpoint::Http(_)) && token.is_none() {
+			return Err("HTTP signers require a token, see `--keystore-token-file`".into())
+		}
+		Ok(Self { endpoint, token })
+	}
+
+	/// Send `request` to the signer and wait for its response.
+	fn call(&self, request: &Request) -> Result<Response, Error> {
+		let body = serde_json::to_vec(request).map_err(|e| Error::Other(e.to_string()))?;
+		let response = match &self.endpoint {
+			Endpoint::Http(address) =>
+				post(address, self.token.as_deref().unwrap_or_default(), &body),
+			Endpoint::Unix(path) => exchange_line(path, &body),
+		}
+		.map_err(|e| {
+			log::warn!("Remote keystore at {} is unavailable: {}", self.endpoint, e);
+			Error::Unavailable
+		})?;
+
+		serde_json::from_slice::<Result<Response, String>>(&response)
+			.map_err(|e| Error::Other(format!("Invalid response from the signer: {}", e)))?
+			.map_err(Error::Other)
+	}
+
+	fn public_keys<P>(&self, id: KeyTypeId, crypto: CryptoTypeId) -> Vec<P>
+	where
+		P: for<'a> TryFrom<&'a [u8]>,
+	{
+		SyncCryptoStore::keys(self, id)
+			.unwrap_or_default()
+			.into_iter()
+			.filter(|key| key.0 == crypto)
+			.filter_map(|key| P::try_from(&key.1[..]).ok())
+			.collect()
+	}
+
+	fn generate_new<P>(
+		&self,
+		id: KeyTypeId,
+		crypto: CryptoTypeId,
+		seed: Option<&str>,
+	) -> Result<P, Error>
+	where
+		P: for<'a> TryFrom<&'a [u8]>,
+	{
+		let request =
+			Request::GenerateNew { key_type: id.0, crypto: crypto.0, seed: seed.map(Into::into) };
+		match self.call(&request)? {
+			Response::Public(public) => P::try_from(&public[..])
+				.map_err(|_| Error::Other("Invalid public key from the signer".into())),
+			_ => Err(unexpected()),
+		}
+	}
+
+	fn sign(&self, request: Request) -> Result<Option<Vec<u8>>, Error> {
+		match self.call(&request)? {
+			Response::Signature(signature) => Ok(signature.map(|s| s.0)),
+			_ => Err(unexpected()),
+		}
+	}
+
+	/// Run `f` on this keystore on a thread that may block, as requests wait for the signer.
+	async fn blocking<R, F>(&self, f: F) -> R
+	where
+		R: Send + 'static,
+		F: FnOnce(&Self) -> R + Send + 'static,
+	{
+		let keystore = self.clone();
+		async_std::task::spawn_blocking(move || f(&keystore)).await
+	}
+}
+
+fn unexpected() -> Error {
+	Error::Other("Unexpected response from the signer".into())
+}
+
+/// POST `body` to the signer at `address`, presenting `token`, and return the response body.
+fn post(address: &str, token: &str, body: &[u8]) -> std::io::Result<Vec<u8>> {
+	let mut stream = TcpStream::connect(address)?;
+	stream.set_read_timeout(Some(TIMEOUT))?;
+	stream.set_write_timeout(Some(TIMEOUT))?;
+
+	write!(
+		stream,
+		"POST / HTTP/1.1\r\nHost: {}\r\nAuthorization: Bearer {}\r\n\
+		 Content-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+		address,
+		token,
+		body.len(),
+	)?;
+	stream.write_all(body)?;
+
+	let mut response = Vec::new();
+	stream.read_to_end(&mut response)?;
+
+	let invalid = |msg| std::io::Error::new(std::io::ErrorKind::InvalidData, msg);
+	let split = response
+		.windows(4)
+		.position(|w| w == b"\r\n\r\n")
+		.ok_or_else(|| invalid("incomplete HTTP response"))?;
+	if !response.starts_with(b"HTTP/1.1 200") {
+		return Err(invalid("unsuccessful HTTP response"))
+	}
+	Ok(response.split_off(split + 4))
+}
+
+/// Send `body` as one line over the Unix socket at `path` and return the line answered.
+fn exchange_line(path: &Path, body: &[u8]) -> std::io::Result<Vec<u8>> {
+	let mut stream = UnixStream::connect(path)?;
+	stream.set_read_timeout(Some(TIMEOUT))?;
+	stream.set_write_timeout(Some(TIMEOUT))?;
+
+	stream.write_all(body)?;
+	stream.write_all(b"\n")?;
+
+	let mut response = Vec::new();
+	BufReader::new(stream).read_until(b'\n', &mut response)?;
+	Ok(response)
+}
+
+impl SyncCryptoStore for RemoteKeystore {
+	fn sr25519_public_keys(&self, id: KeyTypeId) -> Vec<sr25519::Public> {
+		self.public_keys(id, sr25519::CRYPTO_ID)
+	}
+
+	fn sr25519_generate_new(
+		&self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<sr25519::Public, Error> {
+		self.generate_new(id, sr25519::CRYPTO_ID, seed)
+	}
+
+	fn ed25519_public_keys(&self, id: KeyTypeId) -> Vec<ed25519::Public> {
+		self.public_keys(id, ed25519::CRYPTO_ID)
+	}
+
+	fn ed25519_generate_new(
+		&self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<ed25519::Public, Error> {
+		self.generate_new(id, ed25519::CRYPTO_ID, seed)
+	}
+
+	fn ecdsa_public_keys(&self, id: KeyTypeId) -> Vec<ecdsa::Public> {
+		self.public_keys(id, ecdsa::CRYPTO_ID)
+	}
+
+	fn ecdsa_generate_new(
+		&self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<ecdsa::Public, Error> {
+		self.generate_new(id, ecdsa::CRYPTO_ID, seed)
+	}
+
+	fn insert_unknown(&self, id: KeyTypeId, suri: &str, public: &[u8]) -> Result<(), ()> {
+		let request = Request::InsertUnknown {
+			key_type: id.0,
+			suri: suri.into(),
+			public: public.to_vec().into(),
+		};
+		match self.call(&request) {
+			Ok(Response::Done) => Ok(()),
+			_ => Err(()),
+		}
+	}
+
+	fn supported_keys(
+		&self,
+		id: KeyTypeId,
+		keys: Vec<CryptoTypePublicPair>,
+	) -> Result<Vec<CryptoTypePublicPair>, Error> {
+		let available = SyncCryptoStore::keys(self, id)?;
+		Ok(keys.into_iter().filter(|key| available.contains(key)).collect())
+	}
+
+	fn keys(&self, id: KeyTypeId) -> Result<Vec<CryptoTypePublicPair>, Error> {
+		match self.call(&Request::Keys { key_type: id.0 })? {
+			Response::Keys(keys) => Ok(keys
+				.into_iter()
+				.map(|(crypto, public)| CryptoTypePublicPair(CryptoTypeId(crypto), public.0))
+				.collect()),
+			_ => Err(unexpected()),
+		}
+	}
+
+	fn has_keys(&self, public_keys: &[(Vec<u8>, KeyTypeId)]) -> bool {
+		let keys = public_keys.iter().map(|(public, id)| (public.clone().into(), id.0)).collect();
+		matches!(self.call(&Request::HasKeys { keys }), Ok(Response::HasKeys(true)))
+	}
+
+	fn sign_with(
+		&self,
+		id: KeyTypeId,
+		key: &CryptoTypePublicPair,
+		msg: &[u8],
+	) -> Result<Option<Vec<u8>>, Error> {
+		self.sign(Request::SignWith {
+			key_type: id.0,
+			crypto: (key.0).0,
+			public: key.1.clone().into(),
+			message: msg.to_vec().into(),
+		})
+	}
+
+	fn sr25519_vrf_sign(
+		&self,
+		key_type: KeyTypeId,
+		public: &sr25519::Public,
+		transcript_data: VRFTranscriptData,
+	) -> Result<Option<VRFSignature>, Error> {
+		let items = transcript_data
+			.items
+			.into_iter()
+			.map(|(label, value)| {
+				let value = match value {
+					VRFTranscriptValue::Bytes(bytes) => TranscriptValue::Bytes(bytes.into()),
+					VRFTranscriptValue::U64(n) => TranscriptValue::U64(n),
+				};
+				(label.into(), value)
+			})
+			.collect();
+		let request = Request::VrfSign {
+			key_type: key_type.0,
+			public: public.0.to_vec().into(),
+			label: transcript_data.label.to_vec().into(),
+			items,
+		};
+
+		match self.call(&request)? {
+			Response::VrfSignature(None) => Ok(None),
+			Response::VrfSignature(Some((output, proof))) => {
+				let invalid = |_| Error::Other("Invalid VRF signature from the signer".into());
+				Ok(Some(VRFSignature {
+					output: schnorrkel::vrf::VRFOutput::from_bytes(&output).map_err(invalid)?,
+					proof: schnorrkel::vrf::VRFProof::from_bytes(&proof).map_err(invalid)?,
+				}))
+			},
+			_ => Err(unexpected()),
+		}
+	}
+
+	fn ecdsa_sign_prehashed(
+		&self,
+		id: KeyTypeId,
+		public: &ecdsa::Public,
+		msg: &[u8; 32],
+	) -> Result<Option<ecdsa::Signature>, Error> {
+		let signature = self.sign(Request::EcdsaSignPrehashed {
+			key_type: id.0,
+			public: public.0.to_vec().into(),
+			message: msg.to_vec().into(),
+		})?;
+
+		signature
+			.map(|s| {
+				ecdsa::Signature::try_from(&s[..])
+					.map_err(|_| Error::Other("Invalid ECDSA signature from the signer".into()))
+			})
+			.transpose()
+	}
+}
+
+/// The async interface runs the synchronous one on a blocking thread, so that waiting for the
+/// signer does not stall the executor.
+#[async_trait]
+impl CryptoStore for RemoteKeystore {
+	async fn sr25519_public_keys(&self, id: KeyTypeId) -> Vec<sr25519::Public> {
+		self.blocking(move |k| SyncCryptoStore::sr25519_public_keys(k, id)).await
+	}
+
+	async fn sr25519_generate_new(
+		&self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<sr25519::Public, Error> {
+		let seed = seed.map(String::from);
+		self.blocking(move |k| SyncCryptoStore::sr25519_generate_new(k, id, seed.as_deref()))
+			.await
+	}
+
+	async fn ed25519_public_keys(&self, id: KeyTypeId) -> Vec<ed25519::Public> {
+		self.blocking(move |k| SyncCryptoStore::ed25519_public_keys(k, id)).await
+	}
+
+	async fn ed25519_generate_new(
+		&self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<ed25519::Public, Error> {
+		let seed = seed.map(String::from);
+		self.blocking(move |k| SyncCryptoStore::ed25519_generate_new(k, id, seed.as_deref()))
+			.await
+	}
+
+	async fn ecdsa_public_keys(&self, id: KeyTypeId) -> Vec<ecdsa::Public> {
+		self.blocking(move |k| SyncCryptoStore::ecdsa_public_keys(k, id)).await
+	}
+
+	async fn ecdsa_generate_new(
+		&self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<ecdsa::Public, Error> {
+		let seed = seed.map(String::from);
+		self.blocking(move |k| SyncCryptoStore::ecdsa_generate_new(k, id, seed.as_deref()))
+			.await
+	}
+
+	async fn insert_unknown(&self, id: KeyTypeId, suri: &str, public: &[u8]) -> Result<(), ()> {
+		let (suri, public) = (suri.to_string(), public.to_vec());
+		self.blocking(move |k| SyncCryptoStore::insert_unknown(k, id, &suri, &public))
+			.await
+	}
+
+	async fn supported_keys(
+		&self,
+		id: KeyTypeId,
+		keys: Vec<CryptoTypePublicPair>,
+	) -> Result<Vec<CryptoTypePublicPair>, Error> {
+		self.blocking(move |k| SyncCryptoStore::supported_keys(k, id, keys)).await
+	}
+
+	async fn keys(&self, id: KeyTypeId) -> Result<Vec<CryptoTypePublicPair>, Error> {
+		self.blocking(move |k| SyncCryptoStore::keys(k, id)).await
+	}
+
+	async fn has_keys(&self, public_keys: &[(Vec<u8>, KeyTypeId)]) -> bool {
+		let public_keys = public_keys.to_vec();
+		self.blocking(move |k| SyncCryptoStore::has_keys(k, &public_keys)).await
+	}
+
+	async fn sign_with(
+		&self,
+		id: KeyTypeId,
+		key: &CryptoTypePublicPair,
+		msg: &[u8],
+	) -> Result<Option<Vec<u8>>, Error> {
+		let (key, msg) = (key.clone(), msg.to_vec());
+		self.blocking(move |k| SyncCryptoStore::sign_with(k, id, &key, &msg)).await
+	}
+
+	async fn sr25519_vrf_sign(
+		&self,
+		key_type: KeyTypeId,
+		public: &sr25519::Public,
+		transcript_data: VRFTranscriptData,
+	) -> Result<Option<VRFSignature>, Error> {
+		let public = *public;
+		self.blocking(move |k| {
+			SyncCryptoStore::sr25519_vrf_sign(k, key_type, &public, transcript_data)
+		})
+		.await
+	}
+
+	async fn ecdsa_sign_prehashed(
+		&self,
+		id: KeyTypeId,
+		public: &ecdsa::Public,
+		msg: &[u8; 32],
+	) -> Result<Option<ecdsa::Signature>, Error> {
+		let (public, msg) = (public.clone(), *msg);
+		self.blocking(move |k| SyncCryptoStore::ecdsa_sign_prehashed(k, id, &public, &msg))
+			.await
+	}
+}
@@ -0,0 +1,431 @@
+//! A reference signer serving a local keystore to [`RemoteKeystore`](super::RemoteKeystore)s.
+//!
+//! Each connection carries one request: an HTTP POST of the JSON [`Request`], or the JSON request
+//! on a single line of a Unix socket. Connections are handled by a fixed number of worker threads.
+//!
+//! HTTP requests must carry the signer's token as `Authorization: Bearer <token>`. The Unix socket
+//! is only accessible to the signer's user.
+
+use super::{Endpoint, Request, Response, TranscriptValue};
+use sc_cli::{CliConfiguration, SharedParams};
+use sc_keystore::LocalKeystore;
+use sp_core::{
+	crypto::{CryptoTypeId, CryptoTypePublicPair, KeyTypeId, SecretString},
+	ecdsa, ed25519, sr25519,
+};
+use sp_keystore::{
+	vrf::{VRFTranscriptData, VRFTranscriptValue},
+	SyncCryptoStore,
+};
+use std::{
+	convert::TryFrom,
+	fs,
+	io::{self, BufRead, BufReader, Read, Write},
+	net::TcpListener,
+	os::unix::{
+		fs::{DirBuilderExt, PermissionsExt},
+		net::UnixListener,
+	},
+	path::{Path, PathBuf},
+	process,
+	sync::Arc,
+	thread,
+	time::Duration,
+};
+use structopt::StructOpt;
+
+/// Log target of the signer.
+const LOG_TARGET: &str = "remote-signer";
+
+/// The largest request accepted. Requests carry keys and messages to sign, never more than a few
+/// KiB.
+const MAX_REQUEST_LEN: usize = 16 * 1024;
+
+/// The number of connections handled at once.
+const WORKERS: usize = 8;
+
+/// How long a connection may take to send its request and read the response.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The labels of the VRF transcripts the node signs: BABE's, and the items it appends.
+const VRF_LABELS: &[&[u8]] = &[b"BABE"];
+const VRF_ITEM_LABELS: &[&str] = &["slot number", "current epoch", "chain randomness"];
+
+/// The `remote-signer` command.
+#[derive(Debug, StructOpt)]
+pub struct SignerCmd {
+	/// Where to listen: `http://<ip>:<port>` or `unix:<path>`.
+	#[structopt(long)]
+	pub listen: Endpoint,
+
+	/// The keystore directory holding the keys.
+	#[structopt(long, parse(from_os_str))]
+	pub keystore_path: PathBuf,
+
+	/// A file holding the password the keys are encrypted with, if any.
+	#[structopt(long, parse(from_os_str))]
+	pub password_filename: Option<PathBuf>,
+
+	/// A file holding the token clients must present. Required when listening over HTTP.
+	#[structopt(long, parse(from_os_str))]
+	pub token_file: Option<PathBuf>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+}
+
+impl SignerCmd {
+	/// Serve the keystore until the process is stopped.
+	pub fn run(&self) -> sc_cli::Result<()> {
+		let password = self
+			.password_filename
+			.as_ref()
+			.map(|path| fs::read_to_string(path).map(|p| SecretString::new(p.trim_end().into())))
+			.transpose()?;
+		let keystore = LocalKeystore::open(&self.keystore_path, password)
+			.map_err(|e| format!("Failed to open keystore: {}", e))?;
+
+		let token = self
+			.token_file
+			.as_ref()
+			.map(|path| fs::read_to_string(path).map(|t| t.trim().to_string()))
+			.transpose()?;
+
+		let signer = Signer::bind(Arc::new(keystore), &self.listen, token)?;
+		log::info!(
+			target: LOG_TARGET,
+			"Serving keystore {} on {}",
+			self.keystore_path.display(),
+			signer.endpoint()?,
+		);
+		signer.serve();
+		Ok(())
+	}
+}
+
+impl CliConfiguration for SignerCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+}
+
+enum Listener {
+	Http(TcpListener),
+	Unix(UnixListener, PathBuf),
+}
+
+/// Serves the keys of a [`LocalKeystore`].
+pub struct Signer {
+	listener: Listener,
+	keystore: Arc<LocalKeystore>,
+	/// The token HTTP clients must present.
+	token: Option<String>,
+}
+
+/// Bind a Unix socket at `path` that only the current user can connect to.
+///
+/// The socket is created under the process umask, so it is bound in a directory only the current
+/// user can enter and only moved to `path` once its permissions are restricted.
+fn bind_private(path: &Path) -> io::Result<UnixListener> {
+	if path.exists() {
+		return Err(io::Error::new(
+			io::ErrorKind::AddrInUse,
+			format!("{} already exists", path.display()),
+		))
+	}
+	let file_name = path.file_name().ok_or_else(|| {
+		io::Error::new(io::ErrorKind::InvalidInput, "the socket path has no file name")
+	})?;
+	let dir = path.parent().unwrap_or_else(|| Path::new(".")).join(format!(
+		".{}.{}",
+		file_name.to_string_lossy(),
+		process::id()
+	));
+	fs::DirBuilder::new().mode(0o700).create(&dir)?;
+
+	let private_path = dir.join(file_name);
+	let listener = UnixListener::bind(&private_path).and_then(|listener| {
+		fs::set_permissions(&private_path, fs::Permissions::from_mode(0o600))?;
+		fs::rename(&private_path, path)?;
+		Ok(listener)
+	});
+	let _ = fs::remove_file(&private_path);
+	fs::remove_dir(&dir)?;
+	listener
+}
+
+impl Signer {
+	/// Listen on `endpoint`. A port of 0 listens on any free port.
+	///
+	/// HTTP clients must present `token`, which is thus required for HTTP endpoints. A Unix socket
+	/// is made accessible to the current user only.
+	pub fn bind(
+		keystore: Arc<LocalKeystore>,
+		endpoint: &Endpoint,
+		token: Option<String>,
+	) -> io::Result<Self> {
+		let listener = match endpoint {
+			Endpoint::Http(_) if token.as_deref().map_or(true, str::is_empty) =>
+				return Err(io::Error::new(
+					io::ErrorKind::InvalidInput,
+					"a token is required to listen over HTTP",
+				)),
+			Endpoint::Http(address) => Listener::Http(TcpListener::bind(address)?),
+			Endpoint::Unix(path) => Listener::Unix(bind_private(path)?, path.clone()),
+		};
+		Ok(Self { listener, keystore, token })
+	}
+
+	/// The endpoint the signer listens on.
+	pub fn endpoint(&self) -> io::Result<Endpoint> {
+		Ok(match &self.listener {
+			Listener::Http(listener) => Endpoint::Http(listener.local_addr()?.to_string()),
+			Listener::Unix(_, path) => Endpoint::Unix(path.clone()),
+		})
+	}
+
+	/// Handle connections on [`WORKERS`] threads until the process is stopped.
+	pub fn serve(self) {
+		let signer = Arc::new(self);
+		let workers: Vec<_> = (0..WORKERS)
+			.map(|_| {
+				let signer = signer.clone();
+				thread::spawn(move || loop {
+					if let Err(e) = signer.accept() {
+						log::warn!(target: LOG_TARGET, "Failed to accept a connection: {}", e);
+					}
+				})
+			})
+			.collect();
+		for worker in workers {
+			let _ = worker.join();
+		}
+	}
+
+	/// Accept a connection and handle its request.
+	fn accept(&self) -> io::Result<()> {
+		match &self.listener {
+			Listener::Http(listener) => {
+				let (stream, _) = listener.accept()?;
+				stream.set_read_timeout(Some(TIMEOUT))?;
+				stream.set_write_timeout(Some(TIMEOUT))?;
+				self.handle_http(stream);
+			},
+			Listener::Unix(listener, _) => {
+				let (stream, _) = listener.accept()?;
+				stream.set_read_timeout(Some(TIMEOUT))?;
+				stream.set_write_timeout(Some(TIMEOUT))?;
+				self.handle_line(stream);
+			},
+		}
+		Ok(())
+	}
+
+	fn handle_http<S: Read + Write>(&self, mut stream: S) {
+		let result = (|| -> io::Result<()> {
+			let body = match self.read_http(&mut stream)? {
+				Ok(body) => body,
+				Err(status) => return stream.write_all(&status_only(status)),
+			};
+			let response = self.answer(&body);
+			write!(
+				stream,
+				"HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+				 Connection: close\r\n\r\n",
+				response.len(),
+			)?;
+			stream.write_all(&response)
+		})();
+
+		if let Err(e) = result {
+			log::warn!(target: LOG_TARGET, "Failed to serve an HTTP request: {}", e);
+		}
+	}
+
+	/// Read an HTTP request, returning its body or the status rejecting it.
+	fn read_http<S: Read>(&self, stream: S) -> io::Result<Result<Vec<u8>, &'static str>> {
+		// Bounds the headers, and the body along with them.
+		let mut reader = BufReader::new(stream.take(2 * MAX_REQUEST_LEN as u64));
+		let mut line = String::new();
+		reader.read_line(&mut line)?;
+		let is_post = line.starts_with("POST ");
+
+		let mut content_length = None;
+		let mut authorized = false;
+		loop {
+			line.clear();
+			if reader.read_line(&mut line)? == 0 {
+				return Err(io::ErrorKind::UnexpectedEof.into())
+			}
+			let header = line.trim_end();
+			if header.is_empty() {
+				break
+			}
+			if let Some((name, value)) = header.split_once(':') {
+				let value = value.trim();
+				if name.eq_ignore_ascii_case("content-length") {
+					content_length = Some(value.parse::<usize>().ok());
+				} else if name.eq_ignore_ascii_case("authorization") {
+					authorized = self.is_token(value.strip_prefix("Bearer ").unwrap_or_default());
+				}
+			}
+		}
+
+		if !is_post {
+			return Ok(Err("405 Method Not Allowed"))
+		}
+		if !authorized {
+			return Ok(Err("401 Unauthorized"))
+		}
+		let content_length = match content_length {
+			Some(Some(length)) if length <= MAX_REQUEST_LEN => length,
+			_ => return Ok(Err("400 Bad Request")),
+		};
+
+		let mut body = vec![0; content_length];
+		reader.read_exact(&mut body)?;
+		Ok(Ok(body))
+	}
+
+	fn handle_line<S: Read + Write>(&self, mut stream: S) {
+		let result = (|| -> io::Result<()> {
+			let mut request = Vec::new();
+			BufReader::new((&mut stream).take(MAX_REQUEST_LEN as u64))
+				.read_until(b'\n', &mut request)?;
+			let mut response = if request.ends_with(b"\n") {
+				self.answer(&request)
+			} else {
+				let error: Result<Response, _> = Err("The request is too long or incomplete");
+				serde_json::to_vec(&error).expect("errors always serialize; qed")
+			};
+			response.push(b'\n');
+			stream.write_all(&response)
+		})();
+
+		if let Err(e) = result {
+			log::warn!(target: LOG_TARGET, "Failed to serve a request: {}", e);
+		}
+	}
+
+	/// Whether `token` is the signer's, compared in constant time.
+	fn is_token(&self, token: &str) -> bool {
+		self.token.as_ref().map_or(false, |expected| {
+			expected.len() == token.len() &&
+				expected.bytes().zip(token.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+		})
+	}
+
+	/// The JSON response to the JSON `request`.
+	fn answer(&self, request: &[u8]) -> Vec<u8> {
+		let response = serde_json::from_slice(request)
+			.map_err(|e| format!("Invalid request: {}", e))
+			.and_then(|request| self.execute(request));
+		serde_json::to_vec(&response).expect("responses always serialize; qed")
+	}
+
+	fn execute(&self, request: Request) -> Result<Response, String> {
+		let keystore = &*self.keystore;
+		let error = |e: sp_keystore::Error| e.to_string();
+
+		Ok(match request {
+			Request::Keys { key_type } => Response::Keys(
+				SyncCryptoStore::keys(keystore, KeyTypeId(key_type))
+					.map_err(error)?
+					.into_iter()
+					.map(|key| ((key.0).0, key.1.into()))
+					.collect(),
+			),
+			Request::GenerateNew { key_type, crypto, seed } => {
+				let (id, seed) = (KeyTypeId(key_type), seed.as_deref());
+				let public = match CryptoTypeId(crypto) {
+					sr25519::CRYPTO_ID =>
+						keystore.sr25519_generate_new(id, seed).map_err(error)?.0.to_vec(),
+					ed25519::CRYPTO_ID =>
+						keystore.ed25519_generate_new(id, seed).map_err(error)?.0.to_vec(),
+					ecdsa::CRYPTO_ID =>
+						keystore.ecdsa_generate_new(id, seed).map_err(error)?.0.to_vec(),
+					_ => return Err("Unsupported crypto type".into()),
+				};
+				Response::Public(public.into())
+			},
+			Request::InsertUnknown { key_type, suri, public } => {
+				SyncCryptoStore::insert_unknown(keystore, KeyTypeId(key_type), &suri, &public)
+					.map_err(|()| "Failed to insert the key".to_string())?;
+				Response::Done
+			},
+			Request::HasKeys { keys } => {
+				let keys: Vec<_> =
+					keys.into_iter().map(|(public, id)| (public.0, KeyTypeId(id))).collect();
+				Response::HasKeys(SyncCryptoStore::has_keys(keystore, &keys))
+			},
+			Request::SignWith { key_type, crypto, public, message } => {
+				let key = CryptoTypePublicPair(CryptoTypeId(crypto), public.0);
+				let signature =
+					SyncCryptoStore::sign_with(keystore, KeyTypeId(key_type), &key, &message)
+						.map_err(error)?;
+				Response::Signature(signature.map(Into::into))
+			},
+			Request::VrfSign { key_type, public, label, items } => {
+				let public = sr25519::Public::try_from(&public[..])
+					.map_err(|_| "Invalid sr25519 public key".to_string())?;
+				let transcript = VRFTranscriptData {
+					label: known_label(VRF_LABELS, &label[..])?,
+					items: items
+						.into_iter()
+						.map(|(label, value)| {
+							let label = known_label(VRF_ITEM_LABELS, &label[..])?;
+							let value = match value {
+								TranscriptValue::Bytes(bytes) => VRFTranscriptValue::Bytes(bytes.0),
+								TranscriptValue::U64(n) => VRFTranscriptValue::U64(n),
+							};
+							Ok((label, value))
+						})
+						.collect::<Result<_, String>>()?,
+				};
+				let signature = keystore
+					.sr25519_vrf_sign(KeyTypeId(key_type), &public, transcript)
+					.map_err(error)?;
+				Response::VrfSignature(signature.map(|s| {
+					(s.output.to_bytes().to_vec().into(), s.proof.to_bytes().to_vec().into())
+				}))
+			},
+			Request::EcdsaSignPrehashed { key_type, public, message } => {
+				let public = ecdsa::Public::try_from(&public[..])
+					.map_err(|_| "Invalid ECDSA public key".to_string())?;
+				let message = <[u8; 32]>::try_from(&message[..])
+					.map_err(|_| "The message hash must be 32 bytes".to_string())?;
+				let signature = keystore
+					.ecdsa_sign_prehashed(KeyTypeId(key_type), &public, &message)
+					.map_err(error)?;
+				Response::Signature(signature.map(|s| s.0.to_vec().into()))
+			},
+		})
+	}
+}
+
+/// The label among `known` equal to `label`. VRF transcripts take `'static` labels, so only the
+/// labels the node uses are signed.
+fn known_label<T: ?Sized + PartialEq>(
+	known: &[&'static T],
+	label: &T,
+) -> Result<&'static T, String> {
+	known
+		.iter()
+		.find(|known| ***known == *label)
+		.copied()
+		.ok_or_else(|| "Unknown VRF transcript label".to_string())
+}
+
+/// A response with no body.
+fn status_only(status: &str) -> Vec<u8> {
+	format!("HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status).into_bytes()
+}
+
+impl Drop for Signer {
+	fn drop(&mut self) {
+		if let Listener::Unix(_, path) = &self.listener {
+			let _ = fs::remove_file(path);
+		}
+	}
+}
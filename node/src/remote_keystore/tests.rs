@@ -0,0 +1,160 @@
+use super::{signer::Signer, Endpoint, RemoteKeystore};
+use sc_keystore::LocalKeystore;
+use sp_core::{
+	crypto::{CryptoTypePublicPair, KeyTypeId},
+	sr25519, Pair,
+};
+use sp_keystore::{
+	vrf::{VRFTranscriptData, VRFTranscriptValue},
+	SyncCryptoStore,
+};
+use std::{
+	io::{Read, Write},
+	net::TcpStream,
+	os::unix::fs::PermissionsExt,
+	sync::Arc,
+	thread,
+};
+
+const KEY_TYPE: KeyTypeId = KeyTypeId(*b"test");
+const TOKEN: &str = "secret";
+
+/// Start a signer for an empty in-memory keystore on `endpoint`, returning a client of it.
+fn start_signer(endpoint: Endpoint) -> RemoteKeystore {
+	let signer =
+		Signer::bind(Arc::new(LocalKeystore::in_memory()), &endpoint, Some(TOKEN.into())).unwrap();
+	let endpoint = signer.endpoint().unwrap();
+	thread::spawn(move || signer.serve());
+	RemoteKeystore { endpoint, token: Some(TOKEN.into()) }
+}
+
+/// The status line the signer at `keystore` answers `request` with.
+fn status_of(keystore: &RemoteKeystore, request: String) -> String {
+	let address = match &keystore.endpoint {
+		Endpoint::Http(address) => address,
+		Endpoint::Unix(_) => unreachable!("only HTTP requests have a status"),
+	};
+	let mut stream = TcpStream::connect(address).unwrap();
+	stream.write_all(request.as_bytes()).unwrap();
+	let mut response = String::new();
+	stream.read_to_string(&mut response).unwrap();
+	response.lines().next().unwrap_or_default().into()
+}
+
+fn generates_and_signs_with_keys(keystore: RemoteKeystore) {
+	let public = SyncCryptoStore::sr25519_generate_new(&keystore, KEY_TYPE, None).unwrap();
+	assert_eq!(SyncCryptoStore::sr25519_public_keys(&keystore, KEY_TYPE), vec![public]);
+	assert!(SyncCryptoStore::ed25519_public_keys(&keystore, KEY_TYPE).is_empty());
+	assert!(SyncCryptoStore::has_keys(&keystore, &[(public.0.to_vec(), KEY_TYPE)]));
+
+	let key = CryptoTypePublicPair(sr25519::CRYPTO_ID, public.0.to_vec());
+	let signature = SyncCryptoStore::sign_with(&keystore, KEY_TYPE, &key, b"message")
+		.unwrap()
+		.expect("the signer has the key");
+	let signature = sr25519::Signature::from_slice(&signature);
+	assert!(sr25519::Pair::verify(&signature, b"message", &public));
+
+	// Keys the signer does not have cannot sign.
+	let unknown = CryptoTypePublicPair(sr25519::CRYPTO_ID, vec![0; 32]);
+	assert!(matches!(
+		SyncCryptoStore::sign_with(&keystore, KEY_TYPE, &unknown, b"message"),
+		Ok(None)
+	));
+}
+
+#[test]
+fn signs_over_unix_socket() {
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("signer.sock");
+	let keystore = start_signer(Endpoint::Unix(path.clone()));
+	assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+	// The private directory the socket was bound in is gone.
+	assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+	generates_and_signs_with_keys(keystore);
+}
+
+#[test]
+fn refuses_to_replace_an_existing_socket() {
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("signer.sock");
+	let _signer = start_signer(Endpoint::Unix(path.clone()));
+
+	let keystore = Arc::new(LocalKeystore::in_memory());
+	assert!(Signer::bind(keystore, &Endpoint::Unix(path), None).is_err());
+}
+
+#[test]
+fn signs_over_http() {
+	let keystore = start_signer("http://127.0.0.1:0".parse().unwrap());
+	generates_and_signs_with_keys(keystore);
+}
+
+#[test]
+fn inserts_keys_and_signs_vrf_transcripts() {
+	let keystore = start_signer("http://127.0.0.1:0".parse().unwrap());
+	let pair = sr25519::Pair::from_string("//Alice", None).unwrap();
+	SyncCryptoStore::insert_unknown(&keystore, KEY_TYPE, "//Alice", &pair.public().0).unwrap();
+	assert_eq!(SyncCryptoStore::sr25519_public_keys(&keystore, KEY_TYPE), vec![pair.public()]);
+
+	let transcript = || VRFTranscriptData {
+		label: b"BABE",
+		items: vec![("slot number", VRFTranscriptValue::U64(42))],
+	};
+	let signature =
+		SyncCryptoStore::sr25519_vrf_sign(&keystore, KEY_TYPE, &pair.public(), transcript())
+			.unwrap()
+			.expect("the signer has the key");
+	let local = LocalKeystore::in_memory();
+	SyncCryptoStore::insert_unknown(&local, KEY_TYPE, "//Alice", &pair.public().0).unwrap();
+	let expected =
+		SyncCryptoStore::sr25519_vrf_sign(&local, KEY_TYPE, &pair.public(), transcript())
+			.unwrap()
+			.unwrap();
+	assert_eq!(signature.output, expected.output);
+
+	// Only the labels of the node's transcripts are signed.
+	let unknown =
+		VRFTranscriptData { label: b"test", items: vec![("slot", VRFTranscriptValue::U64(42))] };
+	assert!(
+		SyncCryptoStore::sr25519_vrf_sign(&keystore, KEY_TYPE, &pair.public(), unknown).is_err()
+	);
+}
+
+#[test]
+fn http_requires_the_token() {
+	let keystore = start_signer("http://127.0.0.1:0".parse().unwrap());
+	let wrong_token = RemoteKeystore { token: Some("wrong".into()), ..keystore.clone() };
+	assert!(SyncCryptoStore::keys(&wrong_token, KEY_TYPE).is_err());
+	assert!(SyncCryptoStore::keys(&keystore, KEY_TYPE).is_ok());
+
+	assert!(Signer::bind(
+		Arc::new(LocalKeystore::in_memory()),
+		&"http://127.0.0.1:0".parse().unwrap(),
+		None
+	)
+	.is_err());
+	assert!(RemoteKeystore::open("http://127.0.0.1:9955", None).is_err());
+}
+
+#[test]
+fn rejects_bad_content_lengths() {
+	let keystore = start_signer("http://127.0.0.1:0".parse().unwrap());
+	let request = |content_length: &str| {
+		format!(
+			"POST / HTTP/1.1\r\nAuthorization: Bearer {}\r\nContent-Length: {}\r\n\r\n{{}}",
+			TOKEN, content_length,
+		)
+	};
+
+	assert_eq!(status_of(&keystore, request("2")), "HTTP/1.1 200 OK");
+	assert_eq!(status_of(&keystore, request("two")), "HTTP/1.1 400 Bad Request");
+	assert_eq!(status_of(&keystore, request("1000000")), "HTTP/1.1 400 Bad Request");
+}
+
+#[test]
+fn parses_endpoints() {
+	assert_eq!("http://10.0.0.1:9955/".parse(), Ok(Endpoint::Http("10.0.0.1:9955".into())));
+	assert_eq!("unix:///run/signer.sock".parse(), Ok(Endpoint::Unix("/run/signer.sock".into())));
+	assert_eq!("unix:/run/signer.sock".parse(), Ok(Endpoint::Unix("/run/signer.sock".into())));
+	assert!("ws://10.0.0.1:9955".parse::<Endpoint>().is_err());
+}
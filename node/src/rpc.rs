@@ -7,44 +7,124 @@
 
 use std::sync::Arc;
 
-use node_template_runtime::{opaque::Block, AccountId, Balance, Index};
+use futures::channel::mpsc;
+use node_template_runtime::{opaque::Block, AccountId, Balance, Hash, Index};
+use sc_client_api::{Backend, BlockchainEvents, StorageProvider};
+use sc_consensus_manual_seal::{
+	rpc::{ManualSeal, ManualSealApi},
+	EngineCommand,
+};
+use sc_rpc::SubscriptionTaskExecutor;
 pub use sc_rpc_api::DenyUnsafe;
 use sp_api::ProvideRuntimeApi;
 use sp_block_builder::BlockBuilder;
 use sp_blockchain::{Error as BlockChainError, HeaderBackend, HeaderMetadata};
 use sp_transaction_pool::TransactionPool;
 
+mod indexed;
+mod indexer;
+mod light_payment;
+mod reserves;
+mod something;
+
+pub use indexed::{IndexedData, IndexedDataApi};
+pub use indexer::{IndexerApi, IndexerQueries};
+pub use light_payment::{LightTransactionPayment, LightTransactionPaymentApi};
+pub use reserves::{NamedReserve, Reserves, ReservesApi};
+pub use something::{Follow, Something, SomethingApi, StoredSomething};
+
 /// Full client dependencies.
-pub struct FullDeps<C, P> {
+pub struct FullDeps<C, P, B> {
 	/// The client instance to use.
 	pub client: Arc<C>,
 	/// Transaction pool instance.
 	pub pool: Arc<P>,
+	/// The backend, whose offchain storage holds indexed data.
+	pub backend: Arc<B>,
 	/// Whether to deny unsafe calls
 	pub deny_unsafe: DenyUnsafe,
+	/// Executor for RPC subscriptions.
+	pub subscription_executor: SubscriptionTaskExecutor,
+	/// Where `engine_*` RPCs send their commands, if blocks are sealed with manual-seal.
+	pub command_sink: Option<mpsc::Sender<EngineCommand<Hash>>>,
+	/// The embedded indexer, if enabled.
+	pub indexer: Option<Arc<crate::indexer::Indexer>>,
+	/// Whether offchain indexing is enabled, without which the MMR and indexed data RPCs have
+	/// nothing to serve.
+	pub offchain_indexing: bool,
+}
+
+/// Light client dependencies.
+pub struct LightDeps<C, F, P> {
+	/// The client instance to use.
+	pub client: Arc<C>,
+	/// Transaction pool instance.
+	pub pool: Arc<P>,
+	/// Remote access to the blockchain.
+	pub remote_blockchain: Arc<dyn sc_client_api::light::RemoteBlockchain<Block>>,
+	/// Fetches data from full nodes.
+	pub fetcher: Arc<F>,
 }
 
 /// Instantiate all full RPC extensions.
-pub fn create_full<C, P>(deps: FullDeps<C, P>) -> jsonrpc_core::IoHandler<sc_rpc::Metadata>
+pub fn create_full<C, P, B>(deps: FullDeps<C, P, B>) -> jsonrpc_core::IoHandler<sc_rpc::Metadata>
 where
 	C: ProvideRuntimeApi<Block>,
 	C: HeaderBackend<Block> + HeaderMetadata<Block, Error = BlockChainError> + 'static,
+	C: BlockchainEvents<Block> + StorageProvider<Block, B>,
 	C: Send + Sync + 'static,
 	C::Api: substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Index>,
 	C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
+	C::Api: node_template_runtime::NamedReservesApi<Block, AccountId, Balance>,
+	C::Api: pallet_mmr_rpc::MmrRuntimeApi<Block, Hash>,
 	C::Api: BlockBuilder<Block>,
 	P: TransactionPool + 'static,
+	B: Backend<Block> + Send + Sync + 'static,
 {
+	use pallet_mmr_rpc::{Mmr, MmrApi};
 	use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApi};
 	use substrate_frame_rpc_system::{FullSystem, SystemApi};
 
 	let mut io = jsonrpc_core::IoHandler::default();
-	let FullDeps { client, pool, deny_unsafe } = deps;
+	let FullDeps {
+		client,
+		pool,
+		backend,
+		deny_unsafe,
+		subscription_executor,
+		command_sink,
+		indexer,
+		offchain_indexing,
+	} = deps;
 
 	io.extend_with(SystemApi::to_delegate(FullSystem::new(client.clone(), pool, deny_unsafe)));
 
+	// `payment_queryInfo` reports fees in units of the asset the extrinsic pays them in, if any.
 	io.extend_with(TransactionPaymentApi::to_delegate(TransactionPayment::new(client.clone())));
 
+	io.extend_with(ReservesApi::to_delegate(Reserves::new(client.clone())));
+
+	io.extend_with(SomethingApi::to_delegate(Something::new(
+		client.clone(),
+		subscription_executor,
+	)));
+
+	if offchain_indexing {
+		io.extend_with(MmrApi::to_delegate(Mmr::new(client.clone())));
+
+		if let Some(storage) = backend.offchain_storage() {
+			io.extend_with(IndexedDataApi::to_delegate(IndexedData::new(storage)));
+		}
+	}
+
+	if let Some(indexer) = indexer {
+		io.extend_with(IndexerApi::to_delegate(IndexerQueries::new(indexer)));
+	}
+
+	if let Some(command_sink) = command_sink {
+		io.extend_with(ManualSealApi::to_delegate(ManualSeal::new(command_sink)));
+	}
+
 	// Extend this RPC with a custom API by using the following syntax.
 	// `YourRpcStruct` should have a reference to a client, which is needed
 	// to call into the runtime.
@@ -52,3 +132,31 @@ where
 
 	io
 }
+
+/// Instantiate the RPC extensions light clients can serve, answering from full nodes.
+pub fn create_light<C, P, F>(deps: LightDeps<C, F, P>) -> jsonrpc_core::IoHandler<sc_rpc::Metadata>
+where
+	C: HeaderBackend<Block> + Send + Sync + 'static,
+	F: sc_client_api::light::Fetcher<Block> + 'static,
+	P: TransactionPool + 'static,
+{
+	use substrate_frame_rpc_system::{LightSystem, SystemApi};
+
+	let mut io = jsonrpc_core::IoHandler::default();
+	let LightDeps { client, pool, remote_blockchain, fetcher } = deps;
+
+	io.extend_with(SystemApi::<Hash, AccountId, Index>::to_delegate(LightSystem::new(
+		client.clone(),
+		remote_blockchain.clone(),
+		fetcher.clone(),
+		pool,
+	)));
+
+	io.extend_with(LightTransactionPaymentApi::to_delegate(LightTransactionPayment::new(
+		client,
+		remote_blockchain,
+		fetcher,
+	)));
+
+	io
+}
@@ -0,0 +1,49 @@
+//! RPC interface to the data `pallet_template` indexes offchain.
+
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use node_template_runtime::pallet_template::INDEXING_PREFIX;
+use sp_core::{
+	offchain::{OffchainStorage, STORAGE_PREFIX},
+	Bytes,
+};
+
+/// Indexed data RPC methods.
+#[rpc]
+pub trait IndexedDataApi {
+	/// Get the data indexed under `key`, which must start with the template pallet's indexing
+	/// prefix. Returns `None` if nothing is indexed under it, e.g. because the node was started
+	/// without `--enable-offchain-indexing true`.
+	#[rpc(name = "template_indexedData")]
+	fn indexed_data(&self, key: Bytes) -> Result<Option<Bytes>>;
+}
+
+/// Implements [`IndexedDataApi`] by reading the node's offchain database.
+pub struct IndexedData<S> {
+	storage: S,
+}
+
+impl<S> IndexedData<S> {
+	/// Create new `IndexedData` reading from the given offchain storage.
+	pub fn new(storage: S) -> Self {
+		Self { storage }
+	}
+}
+
+impl<S> IndexedDataApi for IndexedData<S>
+where
+	S: OffchainStorage + 'static,
+{
+	fn indexed_data(&self, key: Bytes) -> Result<Option<Bytes>> {
+		// Only serve indexed data, not whatever else offchain workers keep in the database.
+		if !key.starts_with(INDEXING_PREFIX) {
+			return Err(RpcError {
+				code: ErrorCode::InvalidParams,
+				message: "Key is not under the indexing prefix.".into(),
+				data: None,
+			})
+		}
+
+		Ok(self.storage.get(STORAGE_PREFIX, &key).map(Into::into))
+	}
+}
@@ -0,0 +1,78 @@
+//! RPC interface to the embedded indexer.
+
+use std::sync::Arc;
+
+use crate::indexer::{
+	EventFilter, ExtrinsicFilter, IndexedEvent, IndexedExtrinsic, Indexer, Page, Transfer,
+};
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use node_template_runtime::{AccountId, BlockNumber};
+
+/// Indexer RPC methods. Results are ordered newest first, and `page` narrows the blocks searched
+/// and the number of results.
+#[rpc]
+pub trait IndexerApi {
+	/// The number of the last indexed block, or `None` if no block is indexed yet.
+	#[rpc(name = "indexer_lastIndexed")]
+	fn last_indexed(&self) -> Result<Option<BlockNumber>>;
+
+	/// The balance transfers from or to `account`.
+	#[rpc(name = "indexer_transfers")]
+	fn transfers(&self, account: AccountId, page: Option<Page>) -> Result<Vec<Transfer>>;
+
+	/// The extrinsics `filter` selects.
+	#[rpc(name = "indexer_extrinsics")]
+	fn extrinsics(
+		&self,
+		filter: ExtrinsicFilter,
+		page: Option<Page>,
+	) -> Result<Vec<IndexedExtrinsic>>;
+
+	/// The events `filter` selects.
+	#[rpc(name = "indexer_events")]
+	fn events(&self, filter: EventFilter, page: Option<Page>) -> Result<Vec<IndexedEvent>>;
+}
+
+/// Implements [`IndexerApi`] by querying the indexer database.
+pub struct IndexerQueries {
+	indexer: Arc<Indexer>,
+}
+
+impl IndexerQueries {
+	/// Create new `IndexerQueries` querying the given indexer.
+	pub fn new(indexer: Arc<Indexer>) -> Self {
+		Self { indexer }
+	}
+}
+
+impl IndexerApi for IndexerQueries {
+	fn last_indexed(&self) -> Result<Option<BlockNumber>> {
+		let last_indexed = self.indexer.last_indexed().map_err(query_error)?;
+		Ok(last_indexed.map(|(number, _)| number))
+	}
+
+	fn transfers(&self, account: AccountId, page: Option<Page>) -> Result<Vec<Transfer>> {
+		self.indexer.transfers(&account, &page.unwrap_or_default()).map_err(query_error)
+	}
+
+	fn extrinsics(
+		&self,
+		filter: ExtrinsicFilter,
+		page: Option<Page>,
+	) -> Result<Vec<IndexedExtrinsic>> {
+		self.indexer.extrinsics(&filter, &page.unwrap_or_default()).map_err(query_error)
+	}
+
+	fn events(&self, filter: EventFilter, page: Option<Page>) -> Result<Vec<IndexedEvent>> {
+		self.indexer.events(&filter, &page.unwrap_or_default()).map_err(query_error)
+	}
+}
+
+fn query_error(e: rusqlite::Error) -> RpcError {
+	RpcError {
+		code: ErrorCode::ServerError(1),
+		message: "Unable to query the indexer.".into(),
+		data: Some(e.to_string().into()),
+	}
+}
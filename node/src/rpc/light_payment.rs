@@ -0,0 +1,99 @@
+//! `payment_queryInfo` for light clients, which call the runtime on a full node.
+
+use std::sync::Arc;
+
+use codec::{Decode, Encode};
+use futures::prelude::*;
+use jsonrpc_core::{BoxFuture, Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use node_template_runtime::{opaque::Block, Balance};
+use pallet_transaction_payment_rpc_runtime_api::RuntimeDispatchInfo;
+use sc_client_api::light::{future_header, Fetcher, RemoteBlockchain, RemoteCallRequest};
+use sp_blockchain::HeaderBackend;
+use sp_core::Bytes;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+
+/// Transaction payment RPC methods served by light clients.
+#[rpc]
+pub trait LightTransactionPaymentApi<BlockHash, ResponseType> {
+	/// The weight, class and fee of the encoded extrinsic at the given block, or at the best
+	/// block if omitted. The fee is in units of the asset the extrinsic pays it in, if any.
+	#[rpc(name = "payment_queryInfo")]
+	fn query_info(
+		&self,
+		encoded_xt: Bytes,
+		at: Option<BlockHash>,
+	) -> BoxFuture<Result<ResponseType>>;
+}
+
+/// Implements [`LightTransactionPaymentApi`] by calling the runtime's `TransactionPaymentApi` on
+/// a full node.
+pub struct LightTransactionPayment<C, F> {
+	client: Arc<C>,
+	remote_blockchain: Arc<dyn RemoteBlockchain<Block>>,
+	fetcher: Arc<F>,
+}
+
+impl<C, F> LightTransactionPayment<C, F> {
+	/// Create new `LightTransactionPayment` fetching from full nodes with `fetcher`.
+	pub fn new(
+		client: Arc<C>,
+		remote_blockchain: Arc<dyn RemoteBlockchain<Block>>,
+		fetcher: Arc<F>,
+	) -> Self {
+		Self { client, remote_blockchain, fetcher }
+	}
+}
+
+impl<C, F> LightTransactionPaymentApi<<Block as BlockT>::Hash, RuntimeDispatchInfo<Balance>>
+	for LightTransactionPayment<C, F>
+where
+	C: HeaderBackend<Block> + Send + Sync + 'static,
+	F: Fetcher<Block> + 'static,
+{
+	fn query_info(
+		&self,
+		encoded_xt: Bytes,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> BoxFuture<Result<RuntimeDispatchInfo<Balance>>> {
+		let at = at.unwrap_or_else(|| self.client.info().best_hash);
+		let remote_blockchain = self.remote_blockchain.clone();
+		let fetcher = self.fetcher.clone();
+
+		async move {
+			let encoded_len = encoded_xt.len() as u32;
+			let uxt: <Block as BlockT>::Extrinsic =
+				Decode::decode(&mut &*encoded_xt).map_err(|e| RpcError {
+					code: ErrorCode::InvalidParams,
+					message: "Unable to decode the extrinsic.".into(),
+					data: Some(e.to_string().into()),
+				})?;
+
+			let header = future_header(&*remote_blockchain, &*fetcher, BlockId::Hash(at))
+				.await
+				.map_err(|e| query_error(e.to_string()))?
+				.ok_or_else(|| query_error(format!("Unknown block {}", at)))?;
+			let result = fetcher
+				.remote_call(RemoteCallRequest {
+					block: at,
+					header,
+					method: "TransactionPaymentApi_query_info".into(),
+					call_data: (uxt, encoded_len).encode(),
+					retry_count: None,
+				})
+				.await
+				.map_err(|e| query_error(e.to_string()))?;
+
+			RuntimeDispatchInfo::decode(&mut &result[..]).map_err(|e| query_error(e.to_string()))
+		}
+		.boxed()
+	}
+}
+
+fn query_error(data: String) -> RpcError {
+	RpcError {
+		code: ErrorCode::ServerError(1),
+		message: "Unable to query dispatch info.".into(),
+		data: Some(data.into()),
+	}
+}
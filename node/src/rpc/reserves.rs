@@ -0,0 +1,68 @@
+//! RPC interface to the named reserves held by `pallet_balances`.
+
+use std::sync::Arc;
+
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use node_template_runtime::{opaque::Block, AccountId, Balance, NamedReservesApi};
+use serde::{Deserialize, Serialize};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::Bytes;
+use sp_rpc::number::NumberOrHex;
+use sp_runtime::generic::BlockId;
+
+/// An amount held under a named reserve.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NamedReserve {
+	/// The reserve identifier, e.g. `idx/depo` for index deposits.
+	pub id: Bytes,
+	/// The amount held under `id`.
+	pub amount: NumberOrHex,
+}
+
+/// Reserves RPC methods.
+#[rpc]
+pub trait ReservesApi<BlockHash, AccountId> {
+	/// List the named reserves of `who` at the given block, or at the best block if omitted.
+	#[rpc(name = "reserves_named")]
+	fn named(&self, who: AccountId, at: Option<BlockHash>) -> Result<Vec<NamedReserve>>;
+}
+
+/// Implements [`ReservesApi`] by calling into the runtime's `NamedReservesApi`.
+pub struct Reserves<C> {
+	client: Arc<C>,
+}
+
+impl<C> Reserves<C> {
+	/// Create new `Reserves` with the given reference to the client.
+	pub fn new(client: Arc<C>) -> Self {
+		Self { client }
+	}
+}
+
+impl<C> ReservesApi<<Block as sp_runtime::traits::Block>::Hash, AccountId> for Reserves<C>
+where
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+	C::Api: NamedReservesApi<Block, AccountId, Balance>,
+{
+	fn named(
+		&self,
+		who: AccountId,
+		at: Option<<Block as sp_runtime::traits::Block>::Hash>,
+	) -> Result<Vec<NamedReserve>> {
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		let reserves =
+			self.client.runtime_api().named_reserves(&at, who).map_err(|e| RpcError {
+				code: ErrorCode::ServerError(1),
+				message: "Unable to query named reserves.".into(),
+				data: Some(e.to_string().into()),
+			})?;
+
+		Ok(reserves
+			.into_iter()
+			.map(|(id, amount)| NamedReserve { id: id.to_vec().into(), amount: amount.into() })
+			.collect())
+	}
+}
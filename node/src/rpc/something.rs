@@ -0,0 +1,192 @@
+//! RPC subscription to the values `pallet_template` stores.
+
+use std::{marker::PhantomData, sync::Arc};
+
+use crate::storage_keys::events_key;
+use codec::Decode;
+use frame_system::EventRecord;
+use futures::{future, prelude::*, stream::BoxStream};
+use jsonrpc_core::{Error as RpcError, Result};
+use jsonrpc_derive::rpc;
+use jsonrpc_pubsub::{manager::SubscriptionManager, typed::Subscriber, SubscriptionId};
+use node_template_runtime::{opaque::Block, pallet_template, AccountId, BlockNumber, Event, Hash};
+use sc_client_api::{Backend, BlockchainEvents, StorageProvider};
+use sc_rpc::SubscriptionTaskExecutor;
+use serde::{Deserialize, Serialize};
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{generic::BlockId, traits::Header as _};
+
+/// Which blocks a subscription follows.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Follow {
+	/// Each new best block, as it is imported. Best blocks may be retracted later.
+	Best,
+	/// Each finalized block.
+	Finalized,
+}
+
+impl Default for Follow {
+	fn default() -> Self {
+		Self::Best
+	}
+}
+
+/// A value stored by `pallet_template`, from a `SomethingStored` event.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredSomething {
+	/// The stored value.
+	pub value: u32,
+	/// The block that stored it.
+	pub block_hash: Hash,
+	/// The number of that block.
+	pub block_number: BlockNumber,
+	/// The account that stored it.
+	pub author: AccountId,
+}
+
+/// Template pallet subscription RPC methods.
+#[rpc]
+pub trait SomethingApi {
+	/// RPC metadata.
+	type Metadata;
+
+	/// Notify each value stored in the blocks `follow` selects, best ones by default.
+	#[pubsub(subscription = "template_something", subscribe, name = "template_subscribeSomething")]
+	fn subscribe_something(
+		&self,
+		metadata: Self::Metadata,
+		subscriber: Subscriber<StoredSomething>,
+		follow: Option<Follow>,
+	);
+
+	/// Cancel a subscription.
+	#[pubsub(
+		subscription = "template_something",
+		unsubscribe,
+		name = "template_unsubscribeSomething"
+	)]
+	fn unsubscribe_something(
+		&self,
+		metadata: Option<Self::Metadata>,
+		id: SubscriptionId,
+	) -> Result<bool>;
+}
+
+/// Implements [`SomethingApi`] by reading the events of the blocks the client imports or
+/// finalizes.
+pub struct Something<C, B> {
+	client: Arc<C>,
+	manager: SubscriptionManager,
+	_backend: PhantomData<B>,
+}
+
+impl<C, B> Something<C, B> {
+	/// Create new `Something` with the given reference to the client, spawning subscriptions on
+	/// `executor`.
+	pub fn new(client: Arc<C>, executor: SubscriptionTaskExecutor) -> Self {
+		Self {
+			client,
+			manager: SubscriptionManager::new(Arc::new(executor)),
+			_backend: PhantomData,
+		}
+	}
+}
+
+impl<C, B> SomethingApi for Something<C, B>
+where
+	C: BlockchainEvents<Block> + HeaderBackend<Block> + StorageProvider<Block, B>,
+	C: Send + Sync + 'static,
+	B: Backend<Block> + Send + Sync + 'static,
+{
+	type Metadata = sc_rpc::Metadata;
+
+	fn subscribe_something(
+		&self,
+		_metadata: Self::Metadata,
+		subscriber: Subscriber<StoredSomething>,
+		follow: Option<Follow>,
+	) {
+		let blocks = match follow.unwrap_or_default() {
+			Follow::Best => self
+				.client
+				.import_notification_stream()
+				.filter(|notification| future::ready(notification.is_new_best))
+				.map(|notification| (notification.hash, *notification.header.number()))
+				.boxed(),
+			Follow::Finalized => finalized_blocks(self.client.clone()),
+		};
+
+		let client = self.client.clone();
+		let stored = blocks
+			.flat_map(move |(hash, number)| stream::iter(stored_something(&*client, hash, number)));
+
+		self.manager.add(subscriber, |sink| {
+			stored
+				.map(|stored| Ok(Ok::<_, RpcError>(stored)))
+				.forward(sink.sink_map_err(|e| log::warn!("Error sending notifications: {:?}", e)))
+				.map(|_| ())
+		});
+	}
+
+	fn unsubscribe_something(
+		&self,
+		_metadata: Option<Self::Metadata>,
+		id: SubscriptionId,
+	) -> Result<bool> {
+		Ok(self.manager.cancel(id))
+	}
+}
+
+/// Every finalized block, in order. A finality notification covers the blocks finalized since the
+/// previous one, which are only notified once, for the newest of them.
+fn finalized_blocks<C>(client: Arc<C>) -> BoxStream<'static, (Hash, BlockNumber)>
+where
+	C: BlockchainEvents<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+{
+	let mut last_finalized = client.info().finalized_number;
+	client
+		.finality_notification_stream()
+		.flat_map(move |notification| {
+			let finalized = *notification.header.number();
+			let blocks = (last_finalized + 1..=finalized)
+				.filter_map(|number| Some((client.hash(number).ok()??, number)))
+				.collect::<Vec<_>>();
+			last_finalized = last_finalized.max(finalized);
+			stream::iter(blocks)
+		})
+		.boxed()
+}
+
+/// The values stored in the block `hash`.
+fn stored_something<C, B>(client: &C, hash: Hash, number: BlockNumber) -> Vec<StoredSomething>
+where
+	C: StorageProvider<Block, B>,
+	B: Backend<Block>,
+{
+	let events = match client.storage(&BlockId::Hash(hash), &events_key()) {
+		Ok(Some(events)) => events,
+		Ok(None) => return Vec::new(),
+		Err(e) => {
+			log::warn!("Failed to read the events of block {}: {}", hash, e);
+			return Vec::new()
+		},
+	};
+	let records = match Vec::<EventRecord<Event, Hash>>::decode(&mut &events.0[..]) {
+		Ok(records) => records,
+		Err(e) => {
+			log::warn!("Failed to decode the events of block {}: {}", hash, e);
+			return Vec::new()
+		},
+	};
+
+	records
+		.into_iter()
+		.filter_map(|record| match record.event {
+			Event::TemplateModule(pallet_template::Event::SomethingStored(value, author)) =>
+				Some(StoredSomething { value, block_hash: hash, block_number: number, author }),
+			_ => None,
+		})
+		.collect()
+}
@@ -0,0 +1,173 @@
+//! Development sealing: blocks are sealed with manual-seal on demand, instead of being authored
+//! in slots by Aura (or BABE) and finalized by GRANDPA.
+
+use crate::{service::FullClient, storage_keys::timestamp_now_key};
+use codec::Decode;
+use futures::{channel::mpsc, prelude::*};
+use node_template_runtime::{opaque::Block, Hash};
+use sc_client_api::StorageProvider;
+use sc_consensus_manual_seal::{consensus::ConsensusDataProvider, EngineCommand, Error};
+use sp_api::TransactionFor;
+use sp_consensus::BlockImportParams;
+use sp_inherents::InherentData;
+use sp_runtime::{
+	generic::{BlockId, Digest, DigestItem},
+	traits::Block as BlockT,
+};
+use std::{
+	str::FromStr,
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+#[cfg(test)]
+mod tests;
+
+/// How blocks are sealed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sealing {
+	/// Seal a block as soon as a transaction is ready.
+	Instant,
+	/// Only seal blocks when asked to over RPC.
+	Manual,
+	/// Seal a block every given number of milliseconds.
+	Interval(u64),
+}
+
+impl FromStr for Sealing {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Ok(match s {
+			"instant" => Self::Instant,
+			"manual" => Self::Manual,
+			millis => match millis.parse() {
+				Ok(0) => return Err("the sealing interval must be at least 1 millisecond".into()),
+				Ok(millis) => Self::Interval(millis),
+				Err(_) =>
+					return Err(format!(
+						"expected `instant`, `manual` or a number of milliseconds, got `{}`",
+						millis
+					)),
+			},
+		})
+	}
+}
+
+/// The stream of commands driving manual-seal: those received over RPC, plus those `sealing`
+/// issues itself. Blocks sealed by the latter are finalized right away.
+pub fn commands_stream(
+	sealing: Sealing,
+	rpc_commands: mpsc::Receiver<EngineCommand<Hash>>,
+	pool: &sc_transaction_pool::FullPool<Block, crate::service::FullClient>,
+) -> impl Stream<Item = EngineCommand<Hash>> + Send + Unpin {
+	use sp_transaction_pool::TransactionPool;
+
+	let seal = |create_empty| EngineCommand::SealNewBlock {
+		create_empty,
+		finalize: true,
+		parent_hash: None,
+		sender: None,
+	};
+
+	let own_commands = match sealing {
+		Sealing::Instant => pool.import_notification_stream().map(move |_| seal(false)).boxed(),
+		Sealing::Manual => stream::pending().boxed(),
+		Sealing::Interval(millis) => stream::unfold((), move |()| async move {
+			futures_timer::Delay::new(Duration::from_millis(millis)).await;
+			Some((seal(true), ()))
+		})
+		.boxed(),
+	};
+
+	stream::select(rpc_commands, own_commands)
+}
+
+/// The timestamp of the block sealed on top of `parent`: the current time, unless that is less
+/// than a slot after `parent`'s own timestamp.
+///
+/// The runtime requires every block to be in a later slot than its parent, so blocks sealed in
+/// quick succession are dated a slot apart. Reading the parent's timestamp from its state, rather
+/// than remembering the last one sealed, keeps this true across restarts and for blocks sealed on
+/// an older parent.
+pub fn timestamp_on(client: &FullClient, parent: Hash, slot_millis: u64) -> Result<u64, String> {
+	let parent_timestamp = client
+		.storage(&BlockId::Hash(parent), &timestamp_now_key())
+		.map_err(|e| format!("The timestamp of block {} is not available: {}", parent, e))?
+		.map(|now| u64::decode(&mut &now.0[..]))
+		.transpose()
+		.map_err(|e| format!("Failed to decode the timestamp of block {}: {}", parent, e))?
+		// Genesis has no timestamp.
+		.unwrap_or_default();
+	let now = SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.expect("Current time is after the epoch; qed")
+		.as_millis() as u64;
+
+	Ok(next_timestamp(now, parent_timestamp, slot_millis))
+}
+
+fn next_timestamp(now: u64, parent_timestamp: u64, slot_millis: u64) -> u64 {
+	now.max(parent_timestamp + slot_millis)
+}
+
+/// Writes the pre-runtime digest the runtime's consensus pallet reads the slot from.
+///
+/// Nothing checks the digest, as manual-seal blocks are imported without verification; it only
+/// keeps the runtime's slot bookkeeping working. Under BABE, every block is a secondary slot
+/// claimed by the first authority.
+pub struct SlotDigestProvider;
+
+impl ConsensusDataProvider<Block> for SlotDigestProvider {
+	type Transaction = TransactionFor<crate::service::FullClient, Block>;
+
+	fn create_digest(
+		&self,
+		_parent: &<Block as BlockT>::Header,
+		inherents: &InherentData,
+	) -> Result<Digest<Hash>, Error> {
+		#[cfg(not(feature = "babe"))]
+		let item = {
+			use sp_consensus_aura::{
+				digests::CompatibleDigestItem, inherents::INHERENT_IDENTIFIER,
+				sr25519::AuthoritySignature,
+			};
+
+			let slot = slot_from_inherents(inherents, &INHERENT_IDENTIFIER)?;
+			<DigestItem<Hash> as CompatibleDigestItem<AuthoritySignature>>::aura_pre_digest(slot)
+		};
+
+		#[cfg(feature = "babe")]
+		let item = {
+			use sp_consensus_babe::{
+				digests::{CompatibleDigestItem, PreDigest, SecondaryPlainPreDigest},
+				inherents::INHERENT_IDENTIFIER,
+			};
+
+			let slot = slot_from_inherents(inherents, &INHERENT_IDENTIFIER)?;
+			<DigestItem<Hash> as CompatibleDigestItem>::babe_pre_digest(PreDigest::SecondaryPlain(
+				SecondaryPlainPreDigest { authority_index: 0, slot },
+			))
+		};
+
+		Ok(Digest { logs: vec![item] })
+	}
+
+	fn append_block_import(
+		&self,
+		_parent: &<Block as BlockT>::Header,
+		_params: &mut BlockImportParams<Block, Self::Transaction>,
+		_inherents: &InherentData,
+	) -> Result<(), Error> {
+		Ok(())
+	}
+}
+
+fn slot_from_inherents(
+	inherents: &InherentData,
+	identifier: &sp_inherents::InherentIdentifier,
+) -> Result<sp_consensus_slots::Slot, Error> {
+	inherents
+		.get_data(identifier)
+		.map_err(|e| Error::StringError(e.to_string()))?
+		.ok_or_else(|| Error::StringError("No slot in the inherent data".into()))
+}
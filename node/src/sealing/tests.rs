@@ -0,0 +1,30 @@
+use super::{next_timestamp, Sealing};
+
+#[test]
+fn parses_sealing_modes() {
+	assert_eq!("instant".parse(), Ok(Sealing::Instant));
+	assert_eq!("manual".parse(), Ok(Sealing::Manual));
+	assert_eq!("6000".parse(), Ok(Sealing::Interval(6000)));
+}
+
+#[test]
+fn rejects_a_zero_interval() {
+	assert!("0".parse::<Sealing>().unwrap_err().contains("at least 1 millisecond"));
+}
+
+#[test]
+fn rejects_garbage() {
+	for garbage in ["", "Instant", "-1", "1.5", "6s"].iter() {
+		assert!(garbage.parse::<Sealing>().unwrap_err().contains("expected `instant`"));
+	}
+}
+
+#[test]
+fn blocks_are_dated_at_least_a_slot_after_their_parent() {
+	// A parent in the past leaves the current time alone.
+	assert_eq!(next_timestamp(10_000, 2_000, 6_000), 10_000);
+	// A parent dated less than a slot ago, or in the future after sealing in quick succession,
+	// pushes the block to the next slot.
+	assert_eq!(next_timestamp(10_000, 8_000, 6_000), 14_000);
+	assert_eq!(next_timestamp(10_000, 30_000, 6_000), 36_000);
+}
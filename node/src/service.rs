@@ -1,15 +1,27 @@
 //! Service and ServiceFactory implementation. Specialized wrapper over substrate service.
 
+use crate::{
+	backoff::BackoffParams,
+	chain_spec::{Extensions, FinalityParams},
+	health::{Health, HealthParams},
+	indexer::{self, IndexerParams},
+	remote_keystore::{RemoteKeystore, RemoteKeystoreParams},
+	sealing::{self, Sealing},
+};
+use futures::prelude::*;
 use node_template_runtime::{self, opaque::Block, RuntimeApi};
 use sc_client_api::{ExecutorProvider, RemoteBackend};
-use sc_consensus_aura::{ImportQueueParams, SlotProportion, StartAuraParams};
+use sc_consensus_aura::SlotProportion;
+#[cfg(not(feature = "babe"))]
+use sc_consensus_aura::{ImportQueueParams, StartAuraParams};
 use sc_executor::native_executor_instance;
 pub use sc_executor::NativeExecutor;
 use sc_finality_grandpa::SharedVoterState;
-use sc_keystore::LocalKeystore;
+use sc_network::Event;
 use sc_service::{error::Error as ServiceError, Configuration, TaskManager};
 use sc_telemetry::{Telemetry, TelemetryWorker};
 use sp_consensus::SlotData;
+#[cfg(not(feature = "babe"))]
 use sp_consensus_aura::sr25519::AuthorityPair as AuraPair;
 use std::{sync::Arc, time::Duration};
 
@@ -21,9 +33,25 @@ native_executor_instance!(
 	frame_benchmarking::benchmarking::HostFunctions,
 );
 
-type FullClient = sc_service::TFullClient<Block, RuntimeApi, Executor>;
+pub(crate) type FullClient = sc_service::TFullClient<Block, RuntimeApi, Executor>;
 type FullBackend = sc_service::TFullBackend<Block>;
 type FullSelectChain = sc_consensus::LongestChain<FullBackend, Block>;
+type FullGrandpaBlockImport =
+	sc_finality_grandpa::GrandpaBlockImport<FullBackend, Block, FullClient, FullSelectChain>;
+
+/// The block import handed to the authoring engine: GRANDPA's, wrapped by BABE's when built
+/// with the `babe` feature.
+#[cfg(not(feature = "babe"))]
+type FullBlockImport = FullGrandpaBlockImport;
+#[cfg(feature = "babe")]
+type FullBlockImport =
+	sc_consensus_babe::BabeBlockImport<Block, FullClient, FullGrandpaBlockImport>;
+
+/// State shared between the import queue and the authoring task. Aura needs none.
+#[cfg(not(feature = "babe"))]
+type ConsensusLink = ();
+#[cfg(feature = "babe")]
+type ConsensusLink = sc_consensus_babe::BabeLink<Block>;
 
 pub fn new_partial(
 	config: &Configuration,
@@ -35,22 +63,14 @@ pub fn new_partial(
 		sp_consensus::DefaultImportQueue<Block, FullClient>,
 		sc_transaction_pool::FullPool<Block, FullClient>,
 		(
-			sc_finality_grandpa::GrandpaBlockImport<
-				FullBackend,
-				Block,
-				FullClient,
-				FullSelectChain,
-			>,
+			FullBlockImport,
 			sc_finality_grandpa::LinkHalf<Block, FullClient, FullSelectChain>,
+			ConsensusLink,
 			Option<Telemetry>,
 		),
 	>,
 	ServiceError,
 > {
-	if config.keystore_remote.is_some() {
-		return Err(ServiceError::Other(format!("Remote Keystores are not supported.")))
-	}
-
 	let telemetry = config
 		.telemetry_endpoints
 		.clone()
@@ -91,32 +111,73 @@ pub fn new_partial(
 		telemetry.as_ref().map(|x| x.handle()),
 	)?;
 
-	let slot_duration = sc_consensus_aura::slot_duration(&*client)?.slot_duration();
+	#[cfg(not(feature = "babe"))]
+	let (block_import, consensus_link, import_queue) = {
+		let slot_duration = sc_consensus_aura::slot_duration(&*client)?.slot_duration();
 
-	let import_queue =
-		sc_consensus_aura::import_queue::<AuraPair, _, _, _, _, _, _>(ImportQueueParams {
-			block_import: grandpa_block_import.clone(),
-			justification_import: Some(Box::new(grandpa_block_import.clone())),
-			client: client.clone(),
-			create_inherent_data_providers: move |_, ()| async move {
+		let import_queue =
+			sc_consensus_aura::import_queue::<AuraPair, _, _, _, _, _, _>(ImportQueueParams {
+				block_import: grandpa_block_import.clone(),
+				justification_import: Some(Box::new(grandpa_block_import.clone())),
+				client: client.clone(),
+				create_inherent_data_providers: move |_, ()| async move {
+					let timestamp = sp_timestamp::InherentDataProvider::from_system_time();
+
+					let slot =
+						sp_consensus_aura::inherents::InherentDataProvider::from_timestamp_and_duration(
+							*timestamp,
+							slot_duration,
+						);
+
+					Ok((timestamp, slot))
+				},
+				spawner: &task_manager.spawn_essential_handle(),
+				can_author_with: sp_consensus::CanAuthorWithNativeVersion::new(
+					client.executor().clone(),
+				),
+				registry: config.prometheus_registry(),
+				check_for_equivocation: Default::default(),
+				telemetry: telemetry.as_ref().map(|x| x.handle()),
+			})?;
+
+		(grandpa_block_import, (), import_queue)
+	};
+
+	#[cfg(feature = "babe")]
+	let (block_import, consensus_link, import_queue) = {
+		let (block_import, babe_link) = sc_consensus_babe::block_import(
+			sc_consensus_babe::Config::get_or_compute(&*client)?,
+			grandpa_block_import.clone(),
+			client.clone(),
+		)?;
+
+		let slot_duration = babe_link.config().slot_duration();
+
+		let import_queue = sc_consensus_babe::import_queue(
+			babe_link.clone(),
+			block_import.clone(),
+			Some(Box::new(grandpa_block_import)),
+			client.clone(),
+			select_chain.clone(),
+			move |_, ()| async move {
 				let timestamp = sp_timestamp::InherentDataProvider::from_system_time();
 
 				let slot =
-					sp_consensus_aura::inherents::InherentDataProvider::from_timestamp_and_duration(
+					sp_consensus_babe::inherents::InherentDataProvider::from_timestamp_and_duration(
 						*timestamp,
 						slot_duration,
 					);
 
 				Ok((timestamp, slot))
 			},
-			spawner: &task_manager.spawn_essential_handle(),
-			can_author_with: sp_consensus::CanAuthorWithNativeVersion::new(
-				client.executor().clone(),
-			),
-			registry: config.prometheus_registry(),
-			check_for_equivocation: Default::default(),
-			telemetry: telemetry.as_ref().map(|x| x.handle()),
-		})?;
+			&task_manager.spawn_essential_handle(),
+			config.prometheus_registry(),
+			sp_consensus::CanAuthorWithNativeVersion::new(client.executor().clone()),
+			telemetry.as_ref().map(|x| x.handle()),
+		)?;
+
+		(block_import, babe_link, import_queue)
+	};
 
 	Ok(sc_service::PartialComponents {
 		client,
@@ -126,19 +187,48 @@ pub fn new_partial(
 		keystore_container,
 		select_chain,
 		transaction_pool,
-		other: (grandpa_block_import, grandpa_link, telemetry),
+		other: (block_import, grandpa_link, consensus_link, telemetry),
 	})
 }
 
-fn remote_keystore(_url: &String) -> Result<Arc<LocalKeystore>, &'static str> {
-	// FIXME: here would the concrete keystore be built,
-	//        must return a concrete type (NOT `LocalKeystore`) that
-	//        implements `CryptoStore` and `SyncCryptoStore`
-	Err("Remote Keystore not supported.")
+/// The GRANDPA parameters of the chain being run.
+fn finality_params(config: &Configuration) -> FinalityParams {
+	Extensions::try_get(&*config.chain_spec)
+		.map(|extensions| extensions.finality.clone())
+		.unwrap_or_default()
+}
+
+/// Open the indexer database, if enabled, and index finalized blocks into it in the background.
+fn start_indexer(
+	params: &IndexerParams,
+	config: &Configuration,
+	task_manager: &TaskManager,
+	client: &Arc<FullClient>,
+) -> Result<Option<Arc<indexer::Indexer>>, ServiceError> {
+	let indexer = params.open(config).map_err(ServiceError::Other)?;
+	if let Some(indexer) = &indexer {
+		task_manager
+			.spawn_handle()
+			.spawn_blocking("indexer", indexer::follow_finalized(indexer.clone(), client.clone()));
+	}
+	Ok(indexer)
+}
+
+fn remote_keystore(
+	url: &str,
+	params: &RemoteKeystoreParams,
+) -> Result<Arc<RemoteKeystore>, String> {
+	RemoteKeystore::open(url, params.token()?).map(Arc::new)
 }
 
 /// Builds a new service for a full client.
-pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError> {
+pub fn new_full(
+	mut config: Configuration,
+	backoff: BackoffParams,
+	indexer: IndexerParams,
+	health: HealthParams,
+	keystore_params: RemoteKeystoreParams,
+) -> Result<TaskManager, ServiceError> {
 	let sc_service::PartialComponents {
 		client,
 		backend,
@@ -147,11 +237,11 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 		mut keystore_container,
 		select_chain,
 		transaction_pool,
-		other: (block_import, grandpa_link, mut telemetry),
+		other: (block_import, grandpa_link, consensus_link, mut telemetry),
 	} = new_partial(&config)?;
 
 	if let Some(url) = &config.keystore_remote {
-		match remote_keystore(url) {
+		match remote_keystore(url, &keystore_params) {
 			Ok(k) => keystore_container.set_remote_keystore(k),
 			Err(e) =>
 				return Err(ServiceError::Other(format!(
@@ -185,18 +275,41 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 
 	let role = config.role.clone();
 	let force_authoring = config.force_authoring;
-	let backoff_authoring_blocks: Option<()> = None;
 	let name = config.network.node_name.clone();
 	let enable_grandpa = !config.disable_grandpa;
 	let prometheus_registry = config.prometheus_registry().cloned();
+	let backoff_authoring_blocks = backoff.strategy(prometheus_registry.as_ref())?;
+	let finality = finality_params(&config);
+
+	if !config.offchain_worker.indexing_enabled {
+		// `pallet_mmr` only keeps the peaks on chain; the leaves and inner nodes that proofs are
+		// built from, like the payloads `pallet_template` indexes, are only written to the offchain
+		// database during block import if indexing is enabled.
+		log::warn!(
+			"Offchain indexing is disabled: the MMR and indexed data RPCs are not served. \
+			 Start the node with `--enable-offchain-indexing true` to serve them.",
+		);
+	}
+	let offchain_indexing = config.offchain_worker.indexing_enabled;
+
+	let indexer = start_indexer(&indexer, &config, &task_manager, &client)?;
 
 	let rpc_extensions_builder = {
 		let client = client.clone();
 		let pool = transaction_pool.clone();
+		let backend = backend.clone();
 
-		Box::new(move |deny_unsafe, _| {
-			let deps =
-				crate::rpc::FullDeps { client: client.clone(), pool: pool.clone(), deny_unsafe };
+		Box::new(move |deny_unsafe, subscription_executor| {
+			let deps = crate::rpc::FullDeps {
+				client: client.clone(),
+				pool: pool.clone(),
+				backend: backend.clone(),
+				deny_unsafe,
+				subscription_executor,
+				command_sink: None,
+				indexer: indexer.clone(),
+				offchain_indexing,
+			};
 
 			crate::rpc::create_full(deps)
 		})
@@ -217,6 +330,19 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 		telemetry: telemetry.as_mut(),
 	})?;
 
+	if let Some(addr) = health.listen_addr() {
+		let listener = std::net::TcpListener::bind(addr)?;
+		log::info!("Serving health checks on http://{}", addr);
+		let health = Health::new(
+			health,
+			client.clone(),
+			network.clone(),
+			keystore_container.sync_keystore(),
+			role.is_authority(),
+		);
+		task_manager.spawn_handle().spawn("health", health.serve(listener.into()));
+	}
+
 	if role.is_authority() {
 		let proposer_factory = sc_basic_authorship::ProposerFactory::new(
 			task_manager.spawn_handle(),
@@ -229,42 +355,109 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 		let can_author_with =
 			sp_consensus::CanAuthorWithNativeVersion::new(client.executor().clone());
 
-		let slot_duration = sc_consensus_aura::slot_duration(&*client)?;
-		let raw_slot_duration = slot_duration.slot_duration();
+		#[cfg(not(feature = "babe"))]
+		{
+			// Aura shares no state between the import queue and authoring.
+			let () = consensus_link;
+			let slot_duration = sc_consensus_aura::slot_duration(&*client)?;
+			let raw_slot_duration = slot_duration.slot_duration();
+
+			let aura = sc_consensus_aura::start_aura::<AuraPair, _, _, _, _, _, _, _, _, _, _, _>(
+				StartAuraParams {
+					slot_duration,
+					client: client.clone(),
+					select_chain,
+					block_import,
+					proposer_factory,
+					create_inherent_data_providers: move |_, ()| async move {
+						let timestamp = sp_timestamp::InherentDataProvider::from_system_time();
+
+						let slot =
+							sp_consensus_aura::inherents::InherentDataProvider::from_timestamp_and_duration(
+								*timestamp,
+								raw_slot_duration,
+							);
+
+						Ok((timestamp, slot))
+					},
+					force_authoring,
+					backoff_authoring_blocks,
+					keystore: keystore_container.sync_keystore(),
+					can_author_with,
+					sync_oracle: network.clone(),
+					justification_sync_link: network.clone(),
+					block_proposal_slot_portion: SlotProportion::new(2f32 / 3f32),
+					max_block_proposal_slot_portion: None,
+					telemetry: telemetry.as_ref().map(|x| x.handle()),
+				},
+			)?;
+
+			// the AURA authoring task is considered essential, i.e. if it
+			// fails we take down the service with it.
+			task_manager.spawn_essential_handle().spawn_blocking("aura", aura);
+		}
 
-		let aura = sc_consensus_aura::start_aura::<AuraPair, _, _, _, _, _, _, _, _, _, _, _>(
-			StartAuraParams {
-				slot_duration,
+		#[cfg(feature = "babe")]
+		{
+			let slot_duration = consensus_link.config().slot_duration();
+
+			let babe = sc_consensus_babe::start_babe(sc_consensus_babe::BabeParams {
+				keystore: keystore_container.sync_keystore(),
 				client: client.clone(),
 				select_chain,
+				env: proposer_factory,
 				block_import,
-				proposer_factory,
+				sync_oracle: network.clone(),
+				justification_sync_link: network.clone(),
 				create_inherent_data_providers: move |_, ()| async move {
 					let timestamp = sp_timestamp::InherentDataProvider::from_system_time();
 
 					let slot =
-						sp_consensus_aura::inherents::InherentDataProvider::from_timestamp_and_duration(
+						sp_consensus_babe::inherents::InherentDataProvider::from_timestamp_and_duration(
 							*timestamp,
-							raw_slot_duration,
+							slot_duration,
 						);
 
 					Ok((timestamp, slot))
 				},
 				force_authoring,
 				backoff_authoring_blocks,
-				keystore: keystore_container.sync_keystore(),
+				babe_link: consensus_link,
 				can_author_with,
-				sync_oracle: network.clone(),
-				justification_sync_link: network.clone(),
 				block_proposal_slot_portion: SlotProportion::new(2f32 / 3f32),
 				max_block_proposal_slot_portion: None,
 				telemetry: telemetry.as_ref().map(|x| x.handle()),
-			},
-		)?;
+			})?;
+
+			// the BABE authoring task is considered essential, i.e. if it
+			// fails we take down the service with it.
+			task_manager.spawn_essential_handle().spawn_blocking("babe-proposer", babe);
+		}
+	}
+
+	if role.is_authority() {
+		// Publish our addresses under our authority discovery key, and look up those of the other
+		// authorities, so that validators can connect to each other directly.
+		let authority_discovery_role =
+			sc_authority_discovery::Role::PublishAndDiscover(keystore_container.keystore());
+		let dht_event_stream =
+			network.event_stream("authority-discovery").filter_map(|e| async move {
+				match e {
+					Event::Dht(e) => Some(e),
+					_ => None,
+				}
+			});
+		let (authority_discovery_worker, _service) = sc_authority_discovery::new_worker_and_service(
+			client.clone(),
+			network.clone(),
+			Box::pin(dht_event_stream),
+			authority_discovery_role,
+			prometheus_registry.clone(),
+		);
 
-		// the AURA authoring task is considered essential, i.e. if it
-		// fails we take down the service with it.
-		task_manager.spawn_essential_handle().spawn_blocking("aura", aura);
+		task_manager
+			.spawn_handle()
+			.spawn("authority-discovery-worker", authority_discovery_worker.run());
 	}
 
 	// if the node isn't actively participating in consensus then it doesn't
@@ -273,9 +466,8 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 		if role.is_authority() { Some(keystore_container.sync_keystore()) } else { None };
 
 	let grandpa_config = sc_finality_grandpa::Config {
-		// FIXME #1578 make this available through chainspec
-		gossip_duration: Duration::from_millis(333),
-		justification_period: 512,
+		gossip_duration: Duration::from_millis(finality.gossip_duration),
+		justification_period: finality.justification_period,
 		name: Some(name),
 		observer_enabled: false,
 		keystore,
@@ -294,7 +486,9 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 			config: grandpa_config,
 			link: grandpa_link,
 			network,
-			voting_rule: sc_finality_grandpa::VotingRulesBuilder::default().build(),
+			voting_rule: sc_finality_grandpa::VotingRulesBuilder::default()
+				.add(sc_finality_grandpa::BeforeBestBlockBy(finality.finality_delay))
+				.build(),
 			prometheus_registry,
 			shared_voter_state: SharedVoterState::empty(),
 			telemetry: telemetry.as_ref().map(|x| x.handle()),
@@ -312,6 +506,172 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 	Ok(task_manager)
 }
 
+/// Builds a new service for a full client that seals blocks with manual-seal, as `sealing`
+/// dictates, instead of running Aura (or BABE) and GRANDPA. Meant for development and tests.
+pub fn new_manual_seal(
+	config: Configuration,
+	sealing: Sealing,
+	indexer: IndexerParams,
+) -> Result<TaskManager, ServiceError> {
+	let telemetry = config
+		.telemetry_endpoints
+		.clone()
+		.filter(|x| !x.is_empty())
+		.map(|endpoints| -> Result<_, sc_telemetry::Error> {
+			let worker = TelemetryWorker::new(16)?;
+			let telemetry = worker.handle().new_telemetry(endpoints);
+			Ok((worker, telemetry))
+		})
+		.transpose()?;
+
+	let (client, backend, keystore_container, mut task_manager) =
+		sc_service::new_full_parts::<Block, RuntimeApi, Executor>(
+			&config,
+			telemetry.as_ref().map(|(_, telemetry)| telemetry.handle()),
+		)?;
+	let client = Arc::new(client);
+
+	let mut telemetry = telemetry.map(|(worker, telemetry)| {
+		task_manager.spawn_handle().spawn("telemetry", worker.run());
+		telemetry
+	});
+
+	let select_chain = sc_consensus::LongestChain::new(backend.clone());
+
+	let transaction_pool = sc_transaction_pool::BasicPool::new_full(
+		config.transaction_pool.clone(),
+		config.role.is_authority().into(),
+		config.prometheus_registry(),
+		task_manager.spawn_essential_handle(),
+		client.clone(),
+	);
+
+	// Blocks sealed by other nodes are imported without verification.
+	let import_queue = sc_consensus_manual_seal::import_queue(
+		Box::new(client.clone()),
+		&task_manager.spawn_essential_handle(),
+		config.prometheus_registry(),
+	);
+
+	let (network, system_rpc_tx, network_starter) =
+		sc_service::build_network(sc_service::BuildNetworkParams {
+			config: &config,
+			client: client.clone(),
+			transaction_pool: transaction_pool.clone(),
+			spawn_handle: task_manager.spawn_handle(),
+			import_queue,
+			on_demand: None,
+			block_announce_validator_builder: None,
+		})?;
+
+	if config.offchain_worker.enabled {
+		sc_service::build_offchain_workers(
+			&config,
+			task_manager.spawn_handle(),
+			client.clone(),
+			network.clone(),
+		);
+	}
+
+	#[cfg(not(feature = "babe"))]
+	let slot_duration = sc_consensus_aura::slot_duration(&*client)?.slot_duration();
+	#[cfg(feature = "babe")]
+	let slot_duration = sc_consensus_babe::Config::get_or_compute(&*client)?.slot_duration();
+	let prometheus_registry = config.prometheus_registry().cloned();
+	let (command_sink, rpc_commands) = futures::channel::mpsc::channel(1024);
+	let offchain_indexing = config.offchain_worker.indexing_enabled;
+
+	let indexer = start_indexer(&indexer, &config, &task_manager, &client)?;
+
+	let rpc_extensions_builder = {
+		let client = client.clone();
+		let pool = transaction_pool.clone();
+		let backend = backend.clone();
+
+		Box::new(move |deny_unsafe, subscription_executor| {
+			let deps = crate::rpc::FullDeps {
+				client: client.clone(),
+				pool: pool.clone(),
+				backend: backend.clone(),
+				deny_unsafe,
+				subscription_executor,
+				command_sink: Some(command_sink.clone()),
+				indexer: indexer.clone(),
+				offchain_indexing,
+			};
+
+			crate::rpc::create_full(deps)
+		})
+	};
+
+	let _rpc_handlers = sc_service::spawn_tasks(sc_service::SpawnTasksParams {
+		network,
+		client: client.clone(),
+		keystore: keystore_container.sync_keystore(),
+		task_manager: &mut task_manager,
+		transaction_pool: transaction_pool.clone(),
+		rpc_extensions_builder,
+		on_demand: None,
+		remote_blockchain: None,
+		backend,
+		system_rpc_tx,
+		config,
+		telemetry: telemetry.as_mut(),
+	})?;
+
+	let proposer_factory = sc_basic_authorship::ProposerFactory::new(
+		task_manager.spawn_handle(),
+		client.clone(),
+		transaction_pool.clone(),
+		prometheus_registry.as_ref(),
+		telemetry.as_ref().map(|x| x.handle()),
+	);
+
+	let slot_millis = slot_duration.as_millis() as u64;
+	let timestamp_client = client.clone();
+
+	let manual_seal =
+		sc_consensus_manual_seal::run_manual_seal(sc_consensus_manual_seal::ManualSealParams {
+			block_import: client.clone(),
+			env: proposer_factory,
+			client: client.clone(),
+			pool: transaction_pool.pool().clone(),
+			commands_stream: sealing::commands_stream(sealing, rpc_commands, &transaction_pool),
+			select_chain,
+			consensus_data_provider: Some(Box::new(sealing::SlotDigestProvider)),
+			create_inherent_data_providers: move |parent, ()| {
+				let millis = sealing::timestamp_on(&timestamp_client, parent, slot_millis);
+
+				async move {
+					let millis = millis?;
+					let timestamp = sp_timestamp::InherentDataProvider::new(millis.into());
+
+					#[cfg(not(feature = "babe"))]
+					let slot =
+						sp_consensus_aura::inherents::InherentDataProvider::from_timestamp_and_duration(
+							*timestamp,
+							slot_duration,
+						);
+					#[cfg(feature = "babe")]
+					let slot =
+						sp_consensus_babe::inherents::InherentDataProvider::from_timestamp_and_duration(
+							*timestamp,
+							slot_duration,
+						);
+
+					Ok((timestamp, slot))
+				}
+			},
+		});
+
+	// the manual-seal authoring task is considered essential, i.e. if it
+	// fails we take down the service with it.
+	task_manager.spawn_essential_handle().spawn_blocking("manual-seal", manual_seal);
+
+	network_starter.start_network();
+	Ok(task_manager)
+}
+
 /// Builds a new service for a light client.
 pub fn new_light(mut config: Configuration) -> Result<TaskManager, ServiceError> {
 	let telemetry = config
@@ -355,9 +715,10 @@ pub fn new_light(mut config: Configuration) -> Result<TaskManager, ServiceError>
 		telemetry.as_ref().map(|x| x.handle()),
 	)?;
 
-	let slot_duration = sc_consensus_aura::slot_duration(&*client)?.slot_duration();
+	#[cfg(not(feature = "babe"))]
+	let import_queue = {
+		let slot_duration = sc_consensus_aura::slot_duration(&*client)?.slot_duration();
 
-	let import_queue =
 		sc_consensus_aura::import_queue::<AuraPair, _, _, _, _, _, _>(ImportQueueParams {
 			block_import: grandpa_block_import.clone(),
 			justification_import: Some(Box::new(grandpa_block_import.clone())),
@@ -378,7 +739,42 @@ pub fn new_light(mut config: Configuration) -> Result<TaskManager, ServiceError>
 			registry: config.prometheus_registry(),
 			check_for_equivocation: Default::default(),
 			telemetry: telemetry.as_ref().map(|x| x.handle()),
-		})?;
+		})?
+	};
+
+	#[cfg(feature = "babe")]
+	let import_queue = {
+		let (babe_block_import, babe_link) = sc_consensus_babe::block_import(
+			sc_consensus_babe::Config::get_or_compute(&*client)?,
+			grandpa_block_import.clone(),
+			client.clone(),
+		)?;
+
+		let slot_duration = babe_link.config().slot_duration();
+
+		sc_consensus_babe::import_queue(
+			babe_link,
+			babe_block_import,
+			Some(Box::new(grandpa_block_import)),
+			client.clone(),
+			select_chain.clone(),
+			move |_, ()| async move {
+				let timestamp = sp_timestamp::InherentDataProvider::from_system_time();
+
+				let slot =
+					sp_consensus_babe::inherents::InherentDataProvider::from_timestamp_and_duration(
+						*timestamp,
+						slot_duration,
+					);
+
+				Ok((timestamp, slot))
+			},
+			&task_manager.spawn_essential_handle(),
+			config.prometheus_registry(),
+			sp_consensus::NeverCanAuthor,
+			telemetry.as_ref().map(|x| x.handle()),
+		)?
+	};
 
 	let (network, system_rpc_tx, network_starter) =
 		sc_service::build_network(sc_service::BuildNetworkParams {
@@ -403,10 +799,11 @@ pub fn new_light(mut config: Configuration) -> Result<TaskManager, ServiceError>
 	let enable_grandpa = !config.disable_grandpa;
 	if enable_grandpa {
 		let name = config.network.node_name.clone();
+		let finality = finality_params(&config);
 
 		let config = sc_finality_grandpa::Config {
-			gossip_duration: std::time::Duration::from_millis(333),
-			justification_period: 512,
+			gossip_duration: Duration::from_millis(finality.gossip_duration),
+			justification_period: finality.justification_period,
 			name: Some(name),
 			observer_enabled: false,
 			keystore: None,
@@ -420,12 +817,19 @@ pub fn new_light(mut config: Configuration) -> Result<TaskManager, ServiceError>
 		);
 	}
 
+	let rpc_extensions = crate::rpc::create_light(crate::rpc::LightDeps {
+		client: client.clone(),
+		pool: transaction_pool.clone(),
+		remote_blockchain: backend.remote_blockchain(),
+		fetcher: on_demand.clone(),
+	});
+
 	sc_service::spawn_tasks(sc_service::SpawnTasksParams {
 		remote_blockchain: Some(backend.remote_blockchain()),
 		transaction_pool,
 		task_manager: &mut task_manager,
 		on_demand: Some(on_demand),
-		rpc_extensions_builder: Box::new(|_, _| ()),
+		rpc_extensions_builder: Box::new(sc_service::NoopRpcExtensionBuilder(rpc_extensions)),
 		config,
 		client,
 		keystore: keystore_container.sync_keystore(),
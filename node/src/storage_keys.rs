@@ -0,0 +1,13 @@
+//! Keys of the runtime storage items the node reads directly.
+
+use sp_core::{storage::StorageKey, twox_128};
+
+/// The key of `frame_system::Events`.
+pub fn events_key() -> StorageKey {
+	StorageKey([twox_128(b"System"), twox_128(b"Events")].concat())
+}
+
+/// The key of `pallet_timestamp::Now`.
+pub fn timestamp_now_key() -> StorageKey {
+	StorageKey([twox_128(b"Timestamp"), twox_128(b"Now")].concat())
+}
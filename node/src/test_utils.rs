@@ -0,0 +1,8 @@
+//! Helpers shared by the node's unit tests.
+
+use structopt::StructOpt;
+
+/// Parse `args` as the command line of `T`, as if given after the name of the binary.
+pub fn parse_args<T: StructOpt>(args: &[&str]) -> T {
+	T::from_iter(std::iter::once("node-template").chain(args.iter().copied()))
+}
@@ -0,0 +1,337 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+/// Pay transaction fees in fungible assets instead of the native currency.
+///
+/// Root whitelists an asset by giving it a conversion rate, expressed as the amount of asset
+/// units charged per unit of native fee. The [`ChargeAssetTxPayment`] signed extension then
+/// lets the signer pick one of those assets: the fee is computed exactly as
+/// `pallet_transaction_payment` would, converted at the current rate and burned from the
+/// signer's asset balance. Unused weight is refunded in the same asset after dispatch.
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+use codec::{Decode, Encode};
+use frame_support::{
+	traits::tokens::fungibles::{Inspect, Mutate},
+	weights::{DispatchInfo, PostDispatchInfo},
+};
+use pallet_transaction_payment::{ChargeTransactionPayment, OnChargeTransaction};
+use sp_runtime::{
+	traits::{
+		DispatchInfoOf, Dispatchable, PostDispatchInfoOf, SaturatedConversion, Saturating,
+		SignedExtension, Zero,
+	},
+	transaction_validity::{
+		InvalidTransaction, TransactionPriority, TransactionValidity, TransactionValidityError,
+		ValidTransaction,
+	},
+	DispatchResult, FixedPointNumber, FixedPointOperand, FixedU128, RuntimeDebug,
+};
+
+type OnChargeTransactionOf<T> = <T as pallet_transaction_payment::Config>::OnChargeTransaction;
+/// Balance type used by `pallet_transaction_payment` for native fees.
+pub type BalanceOf<T> = <OnChargeTransactionOf<T> as OnChargeTransaction<T>>::Balance;
+/// Identifier of an asset that fees may be paid in.
+pub type AssetIdOf<T> =
+	<<T as Config>::Fungibles as Inspect<<T as frame_system::Config>::AccountId>>::AssetId;
+/// Balance type of the assets that fees may be paid in.
+pub type AssetBalanceOf<T> =
+	<<T as Config>::Fungibles as Inspect<<T as frame_system::Config>::AccountId>>::Balance;
+
+/// Decides whose transactions are free of fees.
+pub trait FeeExemption<AccountId> {
+	/// Whether the transaction fee of `who` is waived.
+	fn is_exempt(who: &AccountId) -> bool;
+}
+
+impl<AccountId> FeeExemption<AccountId> for () {
+	fn is_exempt(_: &AccountId) -> bool {
+		false
+	}
+}
+
+/// The `Pre` of `ChargeTransactionPayment`.
+type NativePre<T> = (
+	BalanceOf<T>,
+	<T as frame_system::Config>::AccountId,
+	<OnChargeTransactionOf<T> as OnChargeTransaction<T>>::LiquidityInfo,
+);
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::{dispatch::DispatchResult, pallet_prelude::*};
+	use frame_system::pallet_prelude::*;
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config + pallet_transaction_payment::Config {
+		/// Because this pallet emits events, it depends on the runtime's definition of an event.
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+		/// The assets that fees may be paid in. Fees are burned from, and refunds minted into,
+		/// the signer's balance.
+		type Fungibles: Mutate<Self::AccountId>;
+		/// Accounts whose transactions are not charged at all.
+		type FeeExemption: FeeExemption<Self::AccountId>;
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	/// Whitelisted fee assets and the number of asset units charged per unit of native fee.
+	#[pallet::storage]
+	#[pallet::getter(fn conversion_rate)]
+	pub type ConversionRate<T: Config> = StorageMap<_, Twox64Concat, AssetIdOf<T>, FixedU128>;
+
+	#[pallet::event]
+	#[pallet::metadata(
+		T::AccountId = "AccountId",
+		AssetIdOf<T> = "AssetId",
+		AssetBalanceOf<T> = "Balance"
+	)]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// An asset may now be used to pay fees at the given rate. [asset_id, rate]
+		ConversionRateSet(AssetIdOf<T>, FixedU128),
+		/// An asset may no longer be used to pay fees. [asset_id]
+		ConversionRateRemoved(AssetIdOf<T>),
+		/// A transaction fee was paid in an asset, net of any refund. [who, asset_id, amount]
+		AssetTxFeePaid(T::AccountId, AssetIdOf<T>, AssetBalanceOf<T>),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// A zero conversion rate would make every transaction free.
+		ZeroRate,
+		/// The asset is not whitelisted for fee payment.
+		UnknownAsset,
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Whitelist `asset_id` for fee payment, or update its conversion rate.
+		///
+		/// The origin must be root.
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+		pub fn set_conversion_rate(
+			origin: OriginFor<T>,
+			asset_id: AssetIdOf<T>,
+			rate: FixedU128,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+			ensure!(!rate.is_zero(), Error::<T>::ZeroRate);
+
+			<ConversionRate<T>>::insert(asset_id, rate);
+
+			Self::deposit_event(Event::ConversionRateSet(asset_id, rate));
+			Ok(())
+		}
+
+		/// Remove `asset_id` from the fee payment whitelist.
+		///
+		/// The origin must be root.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn remove_conversion_rate(
+			origin: OriginFor<T>,
+			asset_id: AssetIdOf<T>,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+			ensure!(<ConversionRate<T>>::contains_key(asset_id), Error::<T>::UnknownAsset);
+
+			<ConversionRate<T>>::remove(asset_id);
+
+			Self::deposit_event(Event::ConversionRateRemoved(asset_id));
+			Ok(())
+		}
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Convert a native fee into `asset_id` at the current rate, or `None` if the asset is not
+	/// whitelisted.
+	pub fn to_asset_balance(
+		asset_id: AssetIdOf<T>,
+		fee: BalanceOf<T>,
+	) -> Option<AssetBalanceOf<T>> {
+		Self::conversion_rate(asset_id).map(|rate| convert::<T>(rate, fee))
+	}
+}
+
+fn convert<T: Config>(rate: FixedU128, fee: BalanceOf<T>) -> AssetBalanceOf<T> {
+	rate.saturating_mul_int(fee.saturated_into::<u128>()).saturated_into()
+}
+
+/// What was withdrawn before dispatch, needed to settle the fee afterwards.
+pub enum InitialPayment<T: Config> {
+	/// The signer is exempt from fees and nothing was withdrawn.
+	Nothing,
+	/// The fee was paid in the native currency by `ChargeTransactionPayment`.
+	Native(NativePre<T>),
+	/// The fee was burned from `who`'s balance of `asset_id`.
+	Asset {
+		who: T::AccountId,
+		asset_id: AssetIdOf<T>,
+		tip: BalanceOf<T>,
+		rate: FixedU128,
+		paid: AssetBalanceOf<T>,
+	},
+}
+
+impl<T: Config> Default for InitialPayment<T> {
+	fn default() -> Self {
+		InitialPayment::Native(Default::default())
+	}
+}
+
+/// Require the transactor to pay for themselves, either in the native currency or in a
+/// whitelisted asset.
+///
+/// This replaces `ChargeTransactionPayment` in the runtime's `SignedExtra`; when `asset_id` is
+/// `None` it behaves exactly like it. Signers exempted by `T::FeeExemption` pay nothing.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, RuntimeDebug)]
+pub struct ChargeAssetTxPayment<T: Config> {
+	#[codec(compact)]
+	tip: BalanceOf<T>,
+	asset_id: Option<AssetIdOf<T>>,
+}
+
+impl<T: Config> ChargeAssetTxPayment<T>
+where
+	BalanceOf<T>: Send + Sync + FixedPointOperand,
+	T::Call: Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo>,
+{
+	/// Create a new extension paying `tip` on top of the fee, in `asset_id` if given.
+	pub fn from(tip: BalanceOf<T>, asset_id: Option<AssetIdOf<T>>) -> Self {
+		Self { tip, asset_id }
+	}
+
+	/// The asset the fee is paid in, if any.
+	pub fn asset_id(&self) -> Option<AssetIdOf<T>> {
+		self.asset_id
+	}
+
+	fn native(&self) -> ChargeTransactionPayment<T> {
+		ChargeTransactionPayment::from(self.tip)
+	}
+
+	/// Compute the fee in `asset_id` and make sure `who` is able to pay it.
+	fn withdrawable_fee(
+		&self,
+		who: &T::AccountId,
+		asset_id: AssetIdOf<T>,
+		info: &DispatchInfoOf<T::Call>,
+		len: usize,
+	) -> Result<(BalanceOf<T>, FixedU128, AssetBalanceOf<T>), TransactionValidityError> {
+		let rate = Pallet::<T>::conversion_rate(asset_id).ok_or(InvalidTransaction::Payment)?;
+		let fee = pallet_transaction_payment::Pallet::<T>::compute_fee(len as u32, info, self.tip);
+		let asset_fee = convert::<T>(rate, fee);
+
+		T::Fungibles::can_withdraw(asset_id, who, asset_fee)
+			.into_result()
+			.map_err(|_| InvalidTransaction::Payment)?;
+
+		Ok((fee, rate, asset_fee))
+	}
+}
+
+impl<T: Config> SignedExtension for ChargeAssetTxPayment<T>
+where
+	BalanceOf<T>: Send + Sync + FixedPointOperand,
+	AssetBalanceOf<T>: Send + Sync,
+	T::Call: Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo>,
+{
+	const IDENTIFIER: &'static str = "ChargeAssetTxPayment";
+	type AccountId = T::AccountId;
+	type Call = T::Call;
+	type AdditionalSigned = ();
+	type Pre = InitialPayment<T>;
+
+	fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	fn validate(
+		&self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> TransactionValidity {
+		if T::FeeExemption::is_exempt(who) {
+			return Ok(ValidTransaction::default())
+		}
+		match self.asset_id {
+			None => self.native().validate(who, call, info, len),
+			Some(asset_id) => {
+				let (fee, _, _) = self.withdrawable_fee(who, asset_id, info, len)?;
+				// Prioritise by the native value of the fee so that paying in an asset neither
+				// gains nor loses position in the pool.
+				Ok(ValidTransaction {
+					priority: fee.saturated_into::<TransactionPriority>(),
+					..Default::default()
+				})
+			},
+		}
+	}
+
+	fn pre_dispatch(
+		self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		if T::FeeExemption::is_exempt(who) {
+			return Ok(InitialPayment::Nothing)
+		}
+		match self.asset_id {
+			None => Ok(InitialPayment::Native(self.native().pre_dispatch(who, call, info, len)?)),
+			Some(asset_id) => {
+				let (_, rate, asset_fee) = self.withdrawable_fee(who, asset_id, info, len)?;
+				let paid = T::Fungibles::burn_from(asset_id, who, asset_fee)
+					.map_err(|_| InvalidTransaction::Payment)?;
+
+				Ok(InitialPayment::Asset { who: who.clone(), asset_id, tip: self.tip, rate, paid })
+			},
+		}
+	}
+
+	fn post_dispatch(
+		pre: Self::Pre,
+		info: &DispatchInfoOf<Self::Call>,
+		post_info: &PostDispatchInfoOf<Self::Call>,
+		len: usize,
+		result: &DispatchResult,
+	) -> Result<(), TransactionValidityError> {
+		match pre {
+			InitialPayment::Nothing => Ok(()),
+			InitialPayment::Native(pre) =>
+				ChargeTransactionPayment::<T>::post_dispatch(pre, info, post_info, len, result),
+			InitialPayment::Asset { who, asset_id, tip, rate, paid } => {
+				let actual_fee = pallet_transaction_payment::Pallet::<T>::compute_actual_fee(
+					len as u32, info, post_info, tip,
+				);
+				// Refund at the rate the fee was charged at, even if root changed it during
+				// dispatch.
+				let refund = paid.saturating_sub(convert::<T>(rate, actual_fee));
+				if !refund.is_zero() {
+					// The refund never exceeds what was just burned, so this can only fail if
+					// the account was reaped by the call itself; the refund is lost then.
+					let _ = T::Fungibles::mint_into(asset_id, &who, refund);
+				}
+
+				Pallet::<T>::deposit_event(Event::AssetTxFeePaid(
+					who,
+					asset_id,
+					paid.saturating_sub(refund),
+				));
+				Ok(())
+			},
+		}
+	}
+}
@@ -0,0 +1,133 @@
+use crate::{mock::*, ChargeAssetTxPayment, Error, Event as AssetTxPaymentEvent};
+use frame_support::{
+	assert_noop, assert_ok,
+	weights::{DispatchInfo, PostDispatchInfo},
+};
+use sp_runtime::{traits::SignedExtension, DispatchError, FixedU128};
+
+const CALL: &<Test as frame_system::Config>::Call =
+	&Call::Balances(pallet_balances::Call::transfer(3, 10));
+
+// Base fee 1 + length fee 10 + weight fee 5.
+const LEN: usize = 10;
+const INFO: DispatchInfo = DispatchInfo {
+	weight: 5,
+	class: frame_support::weights::DispatchClass::Normal,
+	pays_fee: frame_support::weights::Pays::Yes,
+};
+
+fn post_info(actual_weight: u64) -> PostDispatchInfo {
+	PostDispatchInfo { actual_weight: Some(actual_weight), pays_fee: Default::default() }
+}
+
+#[test]
+fn only_root_can_set_conversion_rate() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			AssetTxPayment::set_conversion_rate(Origin::signed(OWNER), ASSET, FixedU128::from(2)),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			AssetTxPayment::set_conversion_rate(Origin::root(), ASSET, FixedU128::from(0)),
+			Error::<Test>::ZeroRate
+		);
+
+		assert_ok!(AssetTxPayment::set_conversion_rate(Origin::root(), ASSET, FixedU128::from(2)));
+		assert_eq!(AssetTxPayment::conversion_rate(ASSET), Some(FixedU128::from(2)));
+
+		assert_ok!(AssetTxPayment::remove_conversion_rate(Origin::root(), ASSET));
+		assert_eq!(AssetTxPayment::conversion_rate(ASSET), None);
+	});
+}
+
+#[test]
+fn fee_is_charged_in_asset_and_unused_weight_refunded() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(AssetTxPayment::set_conversion_rate(Origin::root(), ASSET, FixedU128::from(2)));
+
+		let pre = ChargeAssetTxPayment::<Test>::from(0, Some(ASSET))
+			.pre_dispatch(&CALLER, CALL, &INFO, LEN)
+			.unwrap();
+		// 16 native units at a rate of 2.
+		assert_eq!(Assets::balance(ASSET, CALLER), 1_000 - 32);
+		// Native balance is untouched.
+		assert_eq!(Balances::free_balance(CALLER), 1_000);
+
+		assert_ok!(ChargeAssetTxPayment::<Test>::post_dispatch(
+			pre,
+			&INFO,
+			&post_info(2),
+			LEN,
+			&Ok(())
+		));
+		// Only 13 native units were actually used.
+		assert_eq!(Assets::balance(ASSET, CALLER), 1_000 - 26);
+		System::assert_last_event(Event::AssetTxPayment(AssetTxPaymentEvent::AssetTxFeePaid(
+			CALLER, ASSET, 26,
+		)));
+	});
+}
+
+#[test]
+fn asset_must_be_whitelisted() {
+	new_test_ext().execute_with(|| {
+		assert!(ChargeAssetTxPayment::<Test>::from(0, Some(ASSET))
+			.pre_dispatch(&CALLER, CALL, &INFO, LEN)
+			.is_err());
+		assert_eq!(Assets::balance(ASSET, CALLER), 1_000);
+	});
+}
+
+#[test]
+fn asset_balance_must_cover_fee() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(AssetTxPayment::set_conversion_rate(
+			Origin::root(),
+			ASSET,
+			FixedU128::from(100)
+		));
+
+		assert!(ChargeAssetTxPayment::<Test>::from(0, Some(ASSET))
+			.validate(&CALLER, CALL, &INFO, LEN)
+			.is_err());
+	});
+}
+
+#[test]
+fn native_payment_still_works() {
+	new_test_ext().execute_with(|| {
+		let pre = ChargeAssetTxPayment::<Test>::from(0, None)
+			.pre_dispatch(&CALLER, CALL, &INFO, LEN)
+			.unwrap();
+		assert_eq!(Balances::free_balance(CALLER), 1_000 - 16);
+
+		assert_ok!(ChargeAssetTxPayment::<Test>::post_dispatch(
+			pre,
+			&INFO,
+			&post_info(2),
+			LEN,
+			&Ok(())
+		));
+		assert_eq!(Balances::free_balance(CALLER), 1_000 - 13);
+		assert_eq!(Assets::balance(ASSET, CALLER), 1_000);
+	});
+}
+
+#[test]
+fn exempt_accounts_pay_nothing() {
+	new_test_ext().execute_with(|| {
+		// The exempt account has no funds at all, yet its transactions are valid.
+		let extension = ChargeAssetTxPayment::<Test>::from(0, None);
+		assert_ok!(extension.validate(&EXEMPT, CALL, &INFO, LEN));
+
+		let pre = extension.pre_dispatch(&EXEMPT, CALL, &INFO, LEN).unwrap();
+		assert_ok!(ChargeAssetTxPayment::<Test>::post_dispatch(
+			pre,
+			&INFO,
+			&post_info(2),
+			LEN,
+			&Ok(())
+		));
+		assert_eq!(Balances::free_balance(EXEMPT), 0);
+	});
+}
@@ -0,0 +1,223 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+/// Waive transaction fees for whitelisted accounts.
+///
+/// Root maintains a whitelist of accounts, typically authorities submitting operational calls,
+/// whose transactions are free. To keep a whitelisted account from flooding blocks for free, at
+/// most `MaxFreeCallsPerBlock` transactions are waived per block; further ones pay as usual. Nor
+/// can it flood the transaction pool: it may only have one free transaction there at a time, which
+/// stays valid for `FreeCallLongevity` blocks.
+///
+/// The [`CheckFeeWhitelist`] signed extension does the bookkeeping and must come before the fee
+/// extension in the runtime's `SignedExtra`. The fee extension skips charging whenever
+/// [`Pallet::is_fee_waived`] holds for the signer.
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+use codec::{Decode, Encode};
+use frame_support::{traits::Get, weights::DispatchInfo};
+use sp_runtime::{
+	traits::{DispatchInfoOf, Dispatchable, PostDispatchInfoOf, SignedExtension},
+	transaction_validity::{
+		TransactionLongevity, TransactionValidity, TransactionValidityError, ValidTransaction,
+	},
+	DispatchResult,
+};
+use sp_std::{marker::PhantomData, prelude::*};
+
+#[frame_support::pallet]
+pub mod pallet {
+	use frame_support::{dispatch::DispatchResult, pallet_prelude::*};
+	use frame_system::pallet_prelude::*;
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// Because this pallet emits events, it depends on the runtime's definition of an event.
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+		/// The number of transactions per block whose fees may be waived, across all whitelisted
+		/// accounts.
+		#[pallet::constant]
+		type MaxFreeCallsPerBlock: Get<u32>;
+		/// The number of blocks a transaction whose fee is waived stays valid in the pool.
+		#[pallet::constant]
+		type FreeCallLongevity: Get<TransactionLongevity>;
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	/// Accounts whose transaction fees are waived.
+	#[pallet::storage]
+	pub type Whitelist<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, ()>;
+
+	/// The number of transactions whose fees were waived in the current block.
+	#[pallet::storage]
+	#[pallet::getter(fn free_calls)]
+	pub type FreeCalls<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+	#[pallet::event]
+	#[pallet::metadata(T::AccountId = "AccountId")]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// An account's transaction fees are now waived. [who]
+		AccountWhitelisted(T::AccountId),
+		/// An account's transaction fees are no longer waived. [who]
+		AccountRemoved(T::AccountId),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The account is already whitelisted.
+		AlreadyWhitelisted,
+		/// The account is not whitelisted.
+		NotWhitelisted,
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(_n: T::BlockNumber) -> Weight {
+			<FreeCalls<T>>::kill();
+			T::DbWeight::get().writes(1)
+		}
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Waive the transaction fees of `who`.
+		///
+		/// The origin must be root.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn add_account(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
+			ensure_root(origin)?;
+			ensure!(!<Whitelist<T>>::contains_key(&who), Error::<T>::AlreadyWhitelisted);
+
+			<Whitelist<T>>::insert(&who, ());
+
+			Self::deposit_event(Event::AccountWhitelisted(who));
+			Ok(())
+		}
+
+		/// Stop waiving the transaction fees of `who`.
+		///
+		/// The origin must be root.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn remove_account(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
+			ensure_root(origin)?;
+			ensure!(<Whitelist<T>>::contains_key(&who), Error::<T>::NotWhitelisted);
+
+			<Whitelist<T>>::remove(&who);
+
+			Self::deposit_event(Event::AccountRemoved(who));
+			Ok(())
+		}
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Whether `who` is whitelisted.
+	pub fn is_whitelisted(who: &T::AccountId) -> bool {
+		<Whitelist<T>>::contains_key(who)
+	}
+
+	/// Whether the fee of a transaction signed by `who` is waived, given the quota left in the
+	/// current block.
+	pub fn is_fee_waived(who: &T::AccountId) -> bool {
+		Self::is_whitelisted(who) && Self::free_calls() < T::MaxFreeCallsPerBlock::get()
+	}
+}
+
+/// Count the transactions whose fees are waived against the per-block quota.
+///
+/// The count is only raised after dispatch, so that the fee extension following this one sees the
+/// same quota when deciding whether to charge.
+#[derive(Encode, Decode, Clone, Eq, PartialEq)]
+pub struct CheckFeeWhitelist<T: Config + Send + Sync>(PhantomData<T>);
+
+impl<T: Config + Send + Sync> CheckFeeWhitelist<T> {
+	/// Create a new extension.
+	pub fn new() -> Self {
+		Self(PhantomData)
+	}
+}
+
+impl<T: Config + Send + Sync> Default for CheckFeeWhitelist<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<T: Config + Send + Sync> sp_std::fmt::Debug for CheckFeeWhitelist<T> {
+	#[cfg(feature = "std")]
+	fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+		write!(f, "CheckFeeWhitelist")
+	}
+
+	#[cfg(not(feature = "std"))]
+	fn fmt(&self, _: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+		Ok(())
+	}
+}
+
+impl<T: Config + Send + Sync> SignedExtension for CheckFeeWhitelist<T>
+where
+	T::Call: Dispatchable<Info = DispatchInfo>,
+{
+	const IDENTIFIER: &'static str = "CheckFeeWhitelist";
+	type AccountId = T::AccountId;
+	type Call = T::Call;
+	type AdditionalSigned = ();
+	/// Whether the fee was waived.
+	type Pre = bool;
+
+	fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	fn validate(
+		&self,
+		who: &Self::AccountId,
+		_call: &Self::Call,
+		_info: &DispatchInfoOf<Self::Call>,
+		_len: usize,
+	) -> TransactionValidity {
+		if !Pallet::<T>::is_fee_waived(who) {
+			return Ok(ValidTransaction::default())
+		}
+		// Free transactions cannot be outbid, so the tag keeps each whitelisted account to one of
+		// them in the pool.
+		Ok(ValidTransaction {
+			provides: vec![(Self::IDENTIFIER, who).encode()],
+			longevity: T::FreeCallLongevity::get(),
+			..Default::default()
+		})
+	}
+
+	fn pre_dispatch(
+		self,
+		who: &Self::AccountId,
+		_call: &Self::Call,
+		_info: &DispatchInfoOf<Self::Call>,
+		_len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		Ok(Pallet::<T>::is_fee_waived(who))
+	}
+
+	fn post_dispatch(
+		waived: Self::Pre,
+		_info: &DispatchInfoOf<Self::Call>,
+		_post_info: &PostDispatchInfoOf<Self::Call>,
+		_len: usize,
+		_result: &DispatchResult,
+	) -> Result<(), TransactionValidityError> {
+		if waived {
+			<FreeCalls<T>>::mutate(|count| *count = count.saturating_add(1));
+		}
+		Ok(())
+	}
+}
@@ -0,0 +1,99 @@
+use crate::{mock::*, CheckFeeWhitelist, Error, Event as FeeWhitelistEvent};
+use codec::Encode;
+use frame_support::{
+	assert_noop, assert_ok,
+	traits::{Get, OnInitialize},
+	weights::DispatchInfo,
+};
+use sp_runtime::{traits::SignedExtension, DispatchError};
+
+const CALL: &<Test as frame_system::Config>::Call =
+	&Call::System(frame_system::Call::remark(vec![]));
+
+/// Run a transaction signed by `who` through the extension, returning whether its fee was waived.
+fn apply(who: u64) -> bool {
+	let info = DispatchInfo::default();
+	let waived = CheckFeeWhitelist::<Test>::new().pre_dispatch(&who, CALL, &info, 0).unwrap();
+	assert_ok!(CheckFeeWhitelist::<Test>::post_dispatch(
+		waived,
+		&info,
+		&Default::default(),
+		0,
+		&Ok(())
+	));
+	waived
+}
+
+#[test]
+fn only_root_can_manage_whitelist() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			FeeWhitelist::add_account(Origin::signed(AUTHORITY), OTHER),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			FeeWhitelist::add_account(Origin::root(), AUTHORITY),
+			Error::<Test>::AlreadyWhitelisted
+		);
+
+		assert_ok!(FeeWhitelist::add_account(Origin::root(), OTHER));
+		assert!(FeeWhitelist::is_whitelisted(&OTHER));
+		System::assert_last_event(Event::FeeWhitelist(FeeWhitelistEvent::AccountWhitelisted(
+			OTHER,
+		)));
+
+		assert_ok!(FeeWhitelist::remove_account(Origin::root(), OTHER));
+		assert!(!FeeWhitelist::is_whitelisted(&OTHER));
+		assert_noop!(
+			FeeWhitelist::remove_account(Origin::root(), OTHER),
+			Error::<Test>::NotWhitelisted
+		);
+	});
+}
+
+#[test]
+fn only_whitelisted_accounts_are_waived() {
+	new_test_ext().execute_with(|| {
+		assert!(FeeWhitelist::is_fee_waived(&AUTHORITY));
+		assert!(!FeeWhitelist::is_fee_waived(&OTHER));
+
+		assert!(!apply(OTHER));
+		assert_eq!(FeeWhitelist::free_calls(), 0);
+	});
+}
+
+#[test]
+fn waived_fees_are_limited_per_block() {
+	new_test_ext().execute_with(|| {
+		assert!(apply(AUTHORITY));
+		assert!(apply(AUTHORITY));
+		// The quota of 2 is used up; further transactions pay.
+		assert!(!FeeWhitelist::is_fee_waived(&AUTHORITY));
+		assert!(!apply(AUTHORITY));
+		assert_eq!(FeeWhitelist::free_calls(), 2);
+
+		System::set_block_number(2);
+		FeeWhitelist::on_initialize(2);
+		assert!(apply(AUTHORITY));
+	});
+}
+
+#[test]
+fn free_transactions_are_limited_in_the_pool() {
+	new_test_ext().execute_with(|| {
+		let info = DispatchInfo::default();
+		let validity =
+			CheckFeeWhitelist::<Test>::new().validate(&AUTHORITY, CALL, &info, 0).unwrap();
+		// A second free transaction of the same account provides the same tag, so the pool only
+		// keeps one of them.
+		assert_eq!(
+			validity.provides,
+			vec![(CheckFeeWhitelist::<Test>::IDENTIFIER, AUTHORITY).encode()]
+		);
+		assert_eq!(validity.longevity, FreeCallLongevity::get());
+
+		// Transactions that pay are left to the fee extension.
+		let validity = CheckFeeWhitelist::<Test>::new().validate(&OTHER, CALL, &info, 0).unwrap();
+		assert_eq!(validity, Default::default());
+	});
+}
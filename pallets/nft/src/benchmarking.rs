@@ -0,0 +1,123 @@
+//! Benchmarking setup for pallet-nft
+
+use super::*;
+
+#[allow(unused)]
+use crate::Pallet as Nft;
+use frame_benchmarking::{account, benchmarks, impl_benchmark_test_suite, whitelisted_caller};
+use frame_support::traits::{Currency, Get};
+use frame_system::RawOrigin;
+use sp_runtime::traits::{Bounded, StaticLookup};
+use sp_std::prelude::*;
+
+const SEED: u32 = 0;
+
+fn funded_caller<T: Config>() -> T::AccountId {
+	let caller: T::AccountId = whitelisted_caller();
+	T::Currency::make_free_balance_be(&caller, BalanceOf::<T>::max_value());
+	caller
+}
+
+fn create_collection<T: Config>() -> (T::AccountId, T::CollectionId) {
+	let caller = funded_caller::<T>();
+	let collection = Nft::<T>::next_collection_id();
+	assert!(Nft::<T>::create_collection(RawOrigin::Signed(caller.clone()).into()).is_ok());
+	(caller, collection)
+}
+
+fn mint_item<T: Config>() -> (T::AccountId, T::CollectionId, T::ItemId) {
+	let (caller, collection) = create_collection::<T>();
+	let item = T::ItemId::default();
+	assert!(Nft::<T>::mint(
+		RawOrigin::Signed(caller.clone()).into(),
+		collection,
+		item,
+		T::Lookup::unlookup(caller.clone()),
+	)
+	.is_ok());
+	(caller, collection, item)
+}
+
+benchmarks! {
+	create_collection {
+		let caller = funded_caller::<T>();
+	}: _(RawOrigin::Signed(caller.clone()))
+	verify {
+		assert_eq!(Collections::<T>::get(T::CollectionId::default()).map(|c| c.owner), Some(caller));
+	}
+
+	destroy_collection {
+		let (caller, collection) = create_collection::<T>();
+		let data = vec![0u8; T::StringLimit::get() as usize];
+		Nft::<T>::set_collection_metadata(RawOrigin::Signed(caller.clone()).into(), collection, data)?;
+	}: _(RawOrigin::Signed(caller), collection)
+	verify {
+		assert!(!Collections::<T>::contains_key(collection));
+	}
+
+	mint {
+		let (caller, collection) = create_collection::<T>();
+		let item = T::ItemId::default();
+		let owner: T::AccountId = account("owner", 0, SEED);
+	}: _(RawOrigin::Signed(caller), collection, item, T::Lookup::unlookup(owner.clone()))
+	verify {
+		assert_eq!(Items::<T>::get(collection, item).map(|i| i.owner), Some(owner));
+	}
+
+	burn {
+		let (caller, collection, item) = mint_item::<T>();
+		let data = vec![0u8; T::StringLimit::get() as usize];
+		Nft::<T>::set_item_metadata(RawOrigin::Signed(caller.clone()).into(), collection, item, data)?;
+	}: _(RawOrigin::Signed(caller), collection, item)
+	verify {
+		assert!(!Items::<T>::contains_key(collection, item));
+	}
+
+	transfer {
+		let (caller, collection, item) = mint_item::<T>();
+		let dest: T::AccountId = account("dest", 0, SEED);
+	}: _(RawOrigin::Signed(caller), collection, item, T::Lookup::unlookup(dest.clone()))
+	verify {
+		assert_eq!(Items::<T>::get(collection, item).map(|i| i.owner), Some(dest));
+	}
+
+	approve_transfer {
+		let (caller, collection, item) = mint_item::<T>();
+		let delegate: T::AccountId = account("delegate", 0, SEED);
+	}: _(RawOrigin::Signed(caller), collection, item, T::Lookup::unlookup(delegate.clone()))
+	verify {
+		assert_eq!(Items::<T>::get(collection, item).and_then(|i| i.approved), Some(delegate));
+	}
+
+	cancel_approval {
+		let (caller, collection, item) = mint_item::<T>();
+		let delegate: T::AccountId = account("delegate", 0, SEED);
+		Nft::<T>::approve_transfer(
+			RawOrigin::Signed(caller.clone()).into(),
+			collection,
+			item,
+			T::Lookup::unlookup(delegate),
+		)?;
+	}: _(RawOrigin::Signed(caller), collection, item)
+	verify {
+		assert_eq!(Items::<T>::get(collection, item).and_then(|i| i.approved), None);
+	}
+
+	set_collection_metadata {
+		let (caller, collection) = create_collection::<T>();
+		let data = vec![0u8; T::StringLimit::get() as usize];
+	}: _(RawOrigin::Signed(caller), collection, data)
+	verify {
+		assert!(CollectionMetadataOf::<T>::contains_key(collection));
+	}
+
+	set_item_metadata {
+		let (caller, collection, item) = mint_item::<T>();
+		let data = vec![0u8; T::StringLimit::get() as usize];
+	}: _(RawOrigin::Signed(caller), collection, item, data)
+	verify {
+		assert!(ItemMetadataOf::<T>::contains_key(collection, item));
+	}
+}
+
+impl_benchmark_test_suite!(Nft, crate::mock::new_test_ext(), crate::mock::Test,);
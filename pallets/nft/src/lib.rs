@@ -0,0 +1,494 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+/// Non-fungible tokens grouped into collections.
+///
+/// Anyone may create a collection by reserving a deposit. The collection owner mints items into
+/// it, paying a further deposit per item, and may attach bounded metadata to the collection and
+/// to each item. Item owners can transfer or burn their items, and may approve a single delegate
+/// to transfer an item on their behalf.
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
+pub mod weights;
+pub use weights::WeightInfo;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::WeightInfo;
+	use codec::HasCompact;
+	use frame_support::{
+		dispatch::DispatchResult,
+		pallet_prelude::*,
+		traits::{Currency, NamedReservableCurrency},
+	};
+	use frame_system::pallet_prelude::*;
+	use sp_runtime::traits::{
+		AtLeast32BitUnsigned, CheckedAdd, One, Saturating, StaticLookup, Zero,
+	};
+	use sp_std::{convert::TryInto, prelude::*};
+
+	pub(crate) type BalanceOf<T> =
+		<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+	pub(crate) type ReserveIdentifierOf<T> = <<T as Config>::Currency as NamedReservableCurrency<
+		<T as frame_system::Config>::AccountId,
+	>>::ReserveIdentifier;
+
+	/// Configure the pallet by specifying the parameters and types on which it depends.
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// Because this pallet emits events, it depends on the runtime's definition of an event.
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// The currency in which deposits are reserved.
+		type Currency: NamedReservableCurrency<Self::AccountId>;
+
+		/// The named reserve all deposits of this pallet are held under.
+		type ReserveId: Get<ReserveIdentifierOf<Self>>;
+
+		/// Identifier of a collection. Collection ids are assigned sequentially.
+		type CollectionId: Member + Parameter + Default + Copy + HasCompact + AtLeast32BitUnsigned;
+
+		/// Identifier of an item within a collection, chosen by the minter.
+		type ItemId: Member + Parameter + Default + Copy + HasCompact;
+
+		/// The deposit reserved from the creator of a collection.
+		#[pallet::constant]
+		type CollectionDeposit: Get<BalanceOf<Self>>;
+
+		/// The deposit reserved from the collection owner for each item minted.
+		#[pallet::constant]
+		type ItemDeposit: Get<BalanceOf<Self>>;
+
+		/// The base deposit reserved for setting metadata.
+		#[pallet::constant]
+		type MetadataDepositBase: Get<BalanceOf<Self>>;
+
+		/// The additional deposit reserved per byte of metadata.
+		#[pallet::constant]
+		type MetadataDepositPerByte: Get<BalanceOf<Self>>;
+
+		/// The maximum length of collection and item metadata.
+		#[pallet::constant]
+		type StringLimit: Get<u32>;
+
+		/// Weight information for extrinsics in this pallet.
+		type WeightInfo: WeightInfo;
+	}
+
+	/// Details of a collection.
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug)]
+	pub struct CollectionDetails<AccountId, Balance> {
+		/// The owner of the collection, who may mint items and set metadata.
+		pub owner: AccountId,
+		/// The deposit reserved from `owner` for the collection itself.
+		pub deposit: Balance,
+		/// The number of items currently in the collection.
+		pub items: u32,
+	}
+
+	/// Details of an item.
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug)]
+	pub struct ItemDetails<AccountId, Balance> {
+		/// The owner of the item.
+		pub owner: AccountId,
+		/// An account allowed to transfer the item on the owner's behalf.
+		pub approved: Option<AccountId>,
+		/// The deposit reserved from the collection owner for this item.
+		pub deposit: Balance,
+	}
+
+	/// Metadata attached to a collection or an item.
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug)]
+	pub struct Metadata<Balance, StringLimit: Get<u32>> {
+		/// The deposit reserved from the collection owner for this metadata.
+		pub deposit: Balance,
+		/// Arbitrary data, typically a URI or a hash of off-chain content.
+		pub data: BoundedVec<u8, StringLimit>,
+	}
+
+	/// The storage version. Version 1 holds all deposits under [`Config::ReserveId`].
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::storage_version(STORAGE_VERSION)]
+	pub struct Pallet<T>(_);
+
+	/// The id that will be given to the next collection created.
+	#[pallet::storage]
+	#[pallet::getter(fn next_collection_id)]
+	pub type NextCollectionId<T: Config> = StorageValue<_, T::CollectionId, ValueQuery>;
+
+	/// Details of each collection.
+	#[pallet::storage]
+	#[pallet::getter(fn collection)]
+	pub type Collections<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		T::CollectionId,
+		CollectionDetails<T::AccountId, BalanceOf<T>>,
+	>;
+
+	/// Details of each item, keyed by collection and item id.
+	#[pallet::storage]
+	#[pallet::getter(fn item)]
+	pub type Items<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::CollectionId,
+		Blake2_128Concat,
+		T::ItemId,
+		ItemDetails<T::AccountId, BalanceOf<T>>,
+	>;
+
+	/// Metadata of each collection.
+	#[pallet::storage]
+	#[pallet::getter(fn collection_metadata)]
+	pub type CollectionMetadataOf<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::CollectionId, Metadata<BalanceOf<T>, T::StringLimit>>;
+
+	/// Metadata of each item.
+	#[pallet::storage]
+	#[pallet::getter(fn item_metadata)]
+	pub type ItemMetadataOf<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::CollectionId,
+		Blake2_128Concat,
+		T::ItemId,
+		Metadata<BalanceOf<T>, T::StringLimit>,
+	>;
+
+	#[pallet::event]
+	#[pallet::metadata(
+		T::AccountId = "AccountId",
+		T::CollectionId = "CollectionId",
+		T::ItemId = "ItemId"
+	)]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// A collection was created. [collection, owner]
+		CollectionCreated(T::CollectionId, T::AccountId),
+		/// A collection was destroyed. [collection]
+		CollectionDestroyed(T::CollectionId),
+		/// An item was minted. [collection, item, owner]
+		Issued(T::CollectionId, T::ItemId, T::AccountId),
+		/// An item was transferred. [collection, item, from, to]
+		Transferred(T::CollectionId, T::ItemId, T::AccountId, T::AccountId),
+		/// An item was burned. [collection, item, owner]
+		Burned(T::CollectionId, T::ItemId, T::AccountId),
+		/// An item owner approved a delegate to transfer it. [collection, item, owner, delegate]
+		ApprovedTransfer(T::CollectionId, T::ItemId, T::AccountId, T::AccountId),
+		/// An approval for a delegate to transfer an item was cancelled. [collection, item, owner]
+		ApprovalCancelled(T::CollectionId, T::ItemId, T::AccountId),
+		/// The metadata of a collection was set or cleared. [collection]
+		CollectionMetadataSet(T::CollectionId),
+		/// The metadata of an item was set or cleared. [collection, item]
+		ItemMetadataSet(T::CollectionId, T::ItemId),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The given collection does not exist.
+		UnknownCollection,
+		/// The given item does not exist.
+		UnknownItem,
+		/// The signer is not allowed to perform this operation.
+		NoPermission,
+		/// An item with this id already exists in the collection.
+		AlreadyExists,
+		/// A collection cannot be destroyed while it still holds items.
+		CollectionNotEmpty,
+		/// The metadata is longer than `StringLimit`.
+		MetadataTooLong,
+		/// No more collections can be created.
+		Overflow,
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Create a new collection owned by the signer, reserving `CollectionDeposit`.
+		#[pallet::weight(T::WeightInfo::create_collection())]
+		pub fn create_collection(origin: OriginFor<T>) -> DispatchResult {
+			let owner = ensure_signed(origin)?;
+
+			let collection = Self::next_collection_id();
+			let next = collection.checked_add(&One::one()).ok_or(Error::<T>::Overflow)?;
+
+			let deposit = T::CollectionDeposit::get();
+			T::Currency::reserve_named(&T::ReserveId::get(), &owner, deposit)?;
+
+			<NextCollectionId<T>>::put(next);
+			<Collections<T>>::insert(
+				collection,
+				CollectionDetails { owner: owner.clone(), deposit, items: 0 },
+			);
+
+			Self::deposit_event(Event::CollectionCreated(collection, owner));
+			Ok(())
+		}
+
+		/// Destroy an empty collection and release its deposits.
+		///
+		/// The origin must be the collection owner.
+		#[pallet::weight(T::WeightInfo::destroy_collection())]
+		pub fn destroy_collection(
+			origin: OriginFor<T>,
+			#[pallet::compact] collection: T::CollectionId,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let details = Self::collection(collection).ok_or(Error::<T>::UnknownCollection)?;
+			ensure!(details.owner == who, Error::<T>::NoPermission);
+			ensure!(details.items == 0, Error::<T>::CollectionNotEmpty);
+
+			let metadata_deposit =
+				<CollectionMetadataOf<T>>::take(collection).map_or_else(Zero::zero, |m| m.deposit);
+			T::Currency::unreserve_named(
+				&T::ReserveId::get(),
+				&who,
+				details.deposit.saturating_add(metadata_deposit),
+			);
+			<Collections<T>>::remove(collection);
+
+			Self::deposit_event(Event::CollectionDestroyed(collection));
+			Ok(())
+		}
+
+		/// Mint `item` into `collection` for `owner`, reserving `ItemDeposit` from the signer.
+		///
+		/// The origin must be the collection owner.
+		#[pallet::weight(T::WeightInfo::mint())]
+		pub fn mint(
+			origin: OriginFor<T>,
+			#[pallet::compact] collection: T::CollectionId,
+			#[pallet::compact] item: T::ItemId,
+			owner: <T::Lookup as StaticLookup>::Source,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let owner = T::Lookup::lookup(owner)?;
+
+			<Collections<T>>::try_mutate(collection, |maybe_details| -> DispatchResult {
+				let details = maybe_details.as_mut().ok_or(Error::<T>::UnknownCollection)?;
+				ensure!(details.owner == who, Error::<T>::NoPermission);
+				ensure!(!<Items<T>>::contains_key(collection, item), Error::<T>::AlreadyExists);
+
+				let deposit = T::ItemDeposit::get();
+				T::Currency::reserve_named(&T::ReserveId::get(), &who, deposit)?;
+				details.items = details.items.saturating_add(1);
+
+				<Items<T>>::insert(
+					collection,
+					item,
+					ItemDetails { owner: owner.clone(), approved: None, deposit },
+				);
+				Ok(())
+			})?;
+
+			Self::deposit_event(Event::Issued(collection, item, owner));
+			Ok(())
+		}
+
+		/// Burn `item`, returning its deposits to the collection owner.
+		///
+		/// The origin must be the item owner.
+		#[pallet::weight(T::WeightInfo::burn())]
+		pub fn burn(
+			origin: OriginFor<T>,
+			#[pallet::compact] collection: T::CollectionId,
+			#[pallet::compact] item: T::ItemId,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			<Collections<T>>::try_mutate(collection, |maybe_details| -> DispatchResult {
+				let details = maybe_details.as_mut().ok_or(Error::<T>::UnknownCollection)?;
+				let item_details = Self::item(collection, item).ok_or(Error::<T>::UnknownItem)?;
+				ensure!(item_details.owner == who, Error::<T>::NoPermission);
+
+				let metadata_deposit = <ItemMetadataOf<T>>::take(collection, item)
+					.map_or_else(Zero::zero, |m| m.deposit);
+				T::Currency::unreserve_named(
+					&T::ReserveId::get(),
+					&details.owner,
+					item_details.deposit.saturating_add(metadata_deposit),
+				);
+				details.items = details.items.saturating_sub(1);
+
+				<Items<T>>::remove(collection, item);
+				Ok(())
+			})?;
+
+			Self::deposit_event(Event::Burned(collection, item, who));
+			Ok(())
+		}
+
+		/// Transfer `item` to `dest`.
+		///
+		/// The origin must be the item owner or the delegate approved by them. Any approval is
+		/// cleared by the transfer.
+		#[pallet::weight(T::WeightInfo::transfer())]
+		pub fn transfer(
+			origin: OriginFor<T>,
+			#[pallet::compact] collection: T::CollectionId,
+			#[pallet::compact] item: T::ItemId,
+			dest: <T::Lookup as StaticLookup>::Source,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let dest = T::Lookup::lookup(dest)?;
+
+			let from = <Items<T>>::try_mutate(collection, item, |maybe_details| {
+				let details = maybe_details.as_mut().ok_or(Error::<T>::UnknownItem)?;
+				ensure!(
+					details.owner == who || details.approved.as_ref() == Some(&who),
+					Error::<T>::NoPermission
+				);
+
+				details.approved = None;
+				Ok::<_, DispatchError>(sp_std::mem::replace(&mut details.owner, dest.clone()))
+			})?;
+
+			Self::deposit_event(Event::Transferred(collection, item, from, dest));
+			Ok(())
+		}
+
+		/// Allow `delegate` to transfer `item`, replacing any previous approval.
+		///
+		/// The origin must be the item owner.
+		#[pallet::weight(T::WeightInfo::approve_transfer())]
+		pub fn approve_transfer(
+			origin: OriginFor<T>,
+			#[pallet::compact] collection: T::CollectionId,
+			#[pallet::compact] item: T::ItemId,
+			delegate: <T::Lookup as StaticLookup>::Source,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let delegate = T::Lookup::lookup(delegate)?;
+
+			<Items<T>>::try_mutate(collection, item, |maybe_details| -> DispatchResult {
+				let details = maybe_details.as_mut().ok_or(Error::<T>::UnknownItem)?;
+				ensure!(details.owner == who, Error::<T>::NoPermission);
+
+				details.approved = Some(delegate.clone());
+				Ok(())
+			})?;
+
+			Self::deposit_event(Event::ApprovedTransfer(collection, item, who, delegate));
+			Ok(())
+		}
+
+		/// Cancel the approval for a delegate to transfer `item`.
+		///
+		/// The origin must be the item owner.
+		#[pallet::weight(T::WeightInfo::cancel_approval())]
+		pub fn cancel_approval(
+			origin: OriginFor<T>,
+			#[pallet::compact] collection: T::CollectionId,
+			#[pallet::compact] item: T::ItemId,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			<Items<T>>::try_mutate(collection, item, |maybe_details| -> DispatchResult {
+				let details = maybe_details.as_mut().ok_or(Error::<T>::UnknownItem)?;
+				ensure!(details.owner == who, Error::<T>::NoPermission);
+
+				details.approved = None;
+				Ok(())
+			})?;
+
+			Self::deposit_event(Event::ApprovalCancelled(collection, item, who));
+			Ok(())
+		}
+
+		/// Set the metadata of `collection`, adjusting the reserved deposit. Empty `data` clears
+		/// the metadata and releases its deposit.
+		///
+		/// The origin must be the collection owner.
+		#[pallet::weight(T::WeightInfo::set_collection_metadata())]
+		pub fn set_collection_metadata(
+			origin: OriginFor<T>,
+			#[pallet::compact] collection: T::CollectionId,
+			data: Vec<u8>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let details = Self::collection(collection).ok_or(Error::<T>::UnknownCollection)?;
+			ensure!(details.owner == who, Error::<T>::NoPermission);
+
+			<CollectionMetadataOf<T>>::try_mutate_exists(collection, |metadata| {
+				Self::update_metadata(&who, metadata, data)
+			})?;
+
+			Self::deposit_event(Event::CollectionMetadataSet(collection));
+			Ok(())
+		}
+
+		/// Set the metadata of `item`, adjusting the reserved deposit. Empty `data` clears the
+		/// metadata and releases its deposit.
+		///
+		/// The origin must be the collection owner.
+		#[pallet::weight(T::WeightInfo::set_item_metadata())]
+		pub fn set_item_metadata(
+			origin: OriginFor<T>,
+			#[pallet::compact] collection: T::CollectionId,
+			#[pallet::compact] item: T::ItemId,
+			data: Vec<u8>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let details = Self::collection(collection).ok_or(Error::<T>::UnknownCollection)?;
+			ensure!(details.owner == who, Error::<T>::NoPermission);
+			ensure!(<Items<T>>::contains_key(collection, item), Error::<T>::UnknownItem);
+
+			<ItemMetadataOf<T>>::try_mutate_exists(collection, item, |metadata| {
+				Self::update_metadata(&who, metadata, data)
+			})?;
+
+			Self::deposit_event(Event::ItemMetadataSet(collection, item));
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Replace `metadata` with `data`, reserving or releasing the difference in deposit from
+		/// `depositor`.
+		fn update_metadata(
+			depositor: &T::AccountId,
+			metadata: &mut Option<Metadata<BalanceOf<T>, T::StringLimit>>,
+			data: Vec<u8>,
+		) -> DispatchResult {
+			let old_deposit = metadata.take().map_or_else(Zero::zero, |m| m.deposit);
+			if data.is_empty() {
+				T::Currency::unreserve_named(&T::ReserveId::get(), depositor, old_deposit);
+				return Ok(())
+			}
+
+			let len = data.len() as u32;
+			let data: BoundedVec<u8, T::StringLimit> =
+				data.try_into().map_err(|_| Error::<T>::MetadataTooLong)?;
+			let deposit = T::MetadataDepositPerByte::get()
+				.saturating_mul(len.into())
+				.saturating_add(T::MetadataDepositBase::get());
+
+			if deposit > old_deposit {
+				T::Currency::reserve_named(&T::ReserveId::get(), depositor, deposit - old_deposit)?;
+			} else {
+				T::Currency::unreserve_named(
+					&T::ReserveId::get(),
+					depositor,
+					old_deposit - deposit,
+				);
+			}
+
+			*metadata = Some(Metadata { deposit, data });
+			Ok(())
+		}
+	}
+}
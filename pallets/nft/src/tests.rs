@@ -0,0 +1,123 @@
+use crate::{mock::*, Error};
+use frame_support::{assert_noop, assert_ok, traits::NamedReservableCurrency};
+
+#[test]
+fn create_and_destroy_collection_reserves_deposit() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Nft::create_collection(Origin::signed(1)));
+		assert_eq!(Nft::collection(0).map(|c| c.owner), Some(1));
+		assert_eq!(Nft::next_collection_id(), 1);
+		assert_eq!(Balances::reserved_balance(1), 100);
+
+		assert_noop!(Nft::destroy_collection(Origin::signed(2), 0), Error::<Test>::NoPermission);
+		assert_ok!(Nft::destroy_collection(Origin::signed(1), 0));
+		assert!(Nft::collection(0).is_none());
+		assert_eq!(Balances::reserved_balance(1), 0);
+	});
+}
+
+#[test]
+fn only_collection_owner_can_mint() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Nft::create_collection(Origin::signed(1)));
+
+		assert_noop!(Nft::mint(Origin::signed(2), 0, 42, 2), Error::<Test>::NoPermission);
+		assert_noop!(Nft::mint(Origin::signed(1), 1, 42, 2), Error::<Test>::UnknownCollection);
+
+		assert_ok!(Nft::mint(Origin::signed(1), 0, 42, 2));
+		assert_eq!(Nft::item(0, 42).map(|i| i.owner), Some(2));
+		assert_eq!(Nft::collection(0).map(|c| c.items), Some(1));
+		assert_eq!(Balances::reserved_balance(1), 110);
+		assert_eq!(Balances::reserved_balance_named(&NftReserveId::get(), &1), 110);
+
+		assert_noop!(Nft::mint(Origin::signed(1), 0, 42, 3), Error::<Test>::AlreadyExists);
+		assert_noop!(
+			Nft::destroy_collection(Origin::signed(1), 0),
+			Error::<Test>::CollectionNotEmpty
+		);
+	});
+}
+
+#[test]
+fn transfer_requires_owner_or_approved_delegate() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Nft::create_collection(Origin::signed(1)));
+		assert_ok!(Nft::mint(Origin::signed(1), 0, 42, 2));
+
+		assert_noop!(Nft::transfer(Origin::signed(3), 0, 42, 3), Error::<Test>::NoPermission);
+		assert_noop!(
+			Nft::approve_transfer(Origin::signed(3), 0, 42, 3),
+			Error::<Test>::NoPermission
+		);
+
+		assert_ok!(Nft::approve_transfer(Origin::signed(2), 0, 42, 3));
+		assert_ok!(Nft::transfer(Origin::signed(3), 0, 42, 3));
+		assert_eq!(Nft::item(0, 42).map(|i| i.owner), Some(3));
+		// The approval does not survive the transfer.
+		assert_eq!(Nft::item(0, 42).and_then(|i| i.approved), None);
+
+		assert_ok!(Nft::transfer(Origin::signed(3), 0, 42, 1));
+		assert_eq!(Nft::item(0, 42).map(|i| i.owner), Some(1));
+	});
+}
+
+#[test]
+fn cancelled_approval_cannot_transfer() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Nft::create_collection(Origin::signed(1)));
+		assert_ok!(Nft::mint(Origin::signed(1), 0, 42, 2));
+
+		assert_ok!(Nft::approve_transfer(Origin::signed(2), 0, 42, 3));
+		assert_ok!(Nft::cancel_approval(Origin::signed(2), 0, 42));
+		assert_noop!(Nft::transfer(Origin::signed(3), 0, 42, 3), Error::<Test>::NoPermission);
+	});
+}
+
+#[test]
+fn metadata_is_bounded_and_deposit_tracks_length() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Nft::create_collection(Origin::signed(1)));
+		assert_ok!(Nft::mint(Origin::signed(1), 0, 42, 2));
+
+		assert_noop!(
+			Nft::set_item_metadata(Origin::signed(1), 0, 42, vec![0; 17]),
+			Error::<Test>::MetadataTooLong
+		);
+		assert_noop!(
+			Nft::set_item_metadata(Origin::signed(2), 0, 42, vec![0; 4]),
+			Error::<Test>::NoPermission
+		);
+
+		assert_ok!(Nft::set_item_metadata(Origin::signed(1), 0, 42, vec![0; 10]));
+		assert_eq!(Balances::reserved_balance(1), 110 + 15);
+		assert_ok!(Nft::set_item_metadata(Origin::signed(1), 0, 42, vec![0; 4]));
+		assert_eq!(Balances::reserved_balance(1), 110 + 9);
+		assert_ok!(Nft::set_item_metadata(Origin::signed(1), 0, 42, vec![]));
+		assert!(Nft::item_metadata(0, 42).is_none());
+		assert_eq!(Balances::reserved_balance(1), 110);
+
+		assert_ok!(Nft::set_collection_metadata(Origin::signed(1), 0, b"ipfs://x".to_vec()));
+		assert_eq!(
+			Nft::collection_metadata(0).map(|m| m.data.into_inner()),
+			Some(b"ipfs://x".to_vec())
+		);
+	});
+}
+
+#[test]
+fn burn_releases_deposits_to_collection_owner() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Nft::create_collection(Origin::signed(1)));
+		assert_ok!(Nft::mint(Origin::signed(1), 0, 42, 2));
+		assert_ok!(Nft::set_item_metadata(Origin::signed(1), 0, 42, vec![0; 10]));
+
+		assert_noop!(Nft::burn(Origin::signed(1), 0, 42), Error::<Test>::NoPermission);
+		assert_ok!(Nft::burn(Origin::signed(2), 0, 42));
+		assert!(Nft::item(0, 42).is_none());
+		assert!(Nft::item_metadata(0, 42).is_none());
+		assert_eq!(Nft::collection(0).map(|c| c.items), Some(0));
+		assert_eq!(Balances::reserved_balance(1), 100);
+
+		assert_noop!(Nft::burn(Origin::signed(2), 0, 42), Error::<Test>::UnknownItem);
+	});
+}
@@ -3,6 +3,12 @@
 /// Edit this file to define custom logic or remove it if it is not needed.
 /// Learn more about FRAME and the core library of Substrate FRAME pallets:
 /// <https://substrate.dev/docs/en/knowledgebase/runtime/frame>
+///
+/// Payloads of `do_something` are also written to the offchain database under
+/// [`Pallet::indexing_key`], where indexers can read them without the chain keeping them in state.
+/// Nodes only store them when started with `--enable-offchain-indexing true`.
+///
+/// `store_random` stores a value drawn from the runtime's `T::Randomness`.
 pub use pallet::*;
 
 #[cfg(test)]
@@ -14,6 +20,10 @@ mod tests;
 #[cfg(feature = "runtime-benchmarks")]
 mod benchmarking;
 
+use codec::{Decode, Encode};
+use frame_support::traits::Randomness;
+use sp_std::prelude::*;
+
 #[frame_support::pallet]
 pub mod pallet {
 	use frame_support::{dispatch::DispatchResult, pallet_prelude::*};
@@ -24,6 +34,8 @@ pub mod pallet {
 	pub trait Config: frame_system::Config {
 		/// Because this pallet emits events, it depends on the runtime's definition of an event.
 		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+		/// The source of the values stored by `store_random`.
+		type Randomness: Randomness<Self::Hash, Self::BlockNumber>;
 	}
 
 	#[pallet::pallet]
@@ -75,6 +87,13 @@ pub mod pallet {
 			// Update storage.
 			<Something<T>>::put(something);
 
+			// Index the payload offchain.
+			let key = Self::indexing_key(
+				<frame_system::Pallet<T>>::block_number(),
+				<frame_system::Pallet<T>>::extrinsic_index().unwrap_or_default(),
+			);
+			sp_io::offchain_index::set(&key, &(&who, something).encode());
+
 			// Emit an event.
 			Self::deposit_event(Event::SomethingStored(something, who));
 			// Return a successful DispatchResultWithPostInfo
@@ -99,5 +118,38 @@ pub mod pallet {
 				},
 			}
 		}
+
+		/// An example dispatchable that stores a random value drawn from `T::Randomness`.
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+		pub fn store_random(origin: OriginFor<T>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let something = Self::random_value();
+			<Something<T>>::put(something);
+
+			Self::deposit_event(Event::SomethingStored(something, who));
+			Ok(())
+		}
+	}
+}
+
+/// Prefix of the offchain storage keys under which `do_something` payloads are indexed.
+pub const INDEXING_PREFIX: &[u8] = b"template::something";
+
+impl<T: Config> Pallet<T> {
+	/// The offchain storage key of the payload of the extrinsic at `extrinsic_index` in block
+	/// `block_number`, which is `(who, something)` SCALE-encoded.
+	pub fn indexing_key(block_number: T::BlockNumber, extrinsic_index: u32) -> Vec<u8> {
+		let mut key = INDEXING_PREFIX.to_vec();
+		(block_number, extrinsic_index).using_encoded(|e| key.extend_from_slice(e));
+		key
+	}
+
+	/// The value `store_random` stores in the current block, the first four bytes of the output of
+	/// `T::Randomness`.
+	pub fn random_value() -> u32 {
+		let (random, _) = T::Randomness::random(&b"template::random"[..]);
+		// Hashes are longer than four bytes, so this never falls back to the default.
+		u32::decode(&mut random.as_ref()).unwrap_or_default()
 	}
 }
@@ -1,10 +1,10 @@
 use crate as pallet_template;
-use frame_support::parameter_types;
+use frame_support::{parameter_types, traits::Randomness};
 use frame_system as system;
 use sp_core::H256;
 use sp_runtime::{
 	testing::Header,
-	traits::{BlakeTwo256, IdentityLookup},
+	traits::{BlakeTwo256, Hash, IdentityLookup},
 };
 
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
@@ -53,8 +53,18 @@ impl system::Config for Test {
 	type OnSetCode = ();
 }
 
+/// Randomness that is the hash of the subject, so tests can predict it.
+pub struct TestRandomness;
+
+impl Randomness<H256, u64> for TestRandomness {
+	fn random(subject: &[u8]) -> (H256, u64) {
+		(BlakeTwo256::hash(subject), 0)
+	}
+}
+
 impl pallet_template::Config for Test {
 	type Event = Event;
+	type Randomness = TestRandomness;
 }
 
 // Build genesis storage according to the mock runtime.
@@ -1,5 +1,6 @@
 use crate::{mock::*, Error};
-use frame_support::{assert_noop, assert_ok};
+use codec::{Decode, Encode};
+use frame_support::{assert_noop, assert_ok, traits::Randomness};
 
 #[test]
 fn it_works_for_default_value() {
@@ -18,3 +19,30 @@ fn correct_error_for_none_value() {
 		assert_noop!(TemplateModule::cause_error(Origin::signed(1)), Error::<Test>::NoneValue);
 	});
 }
+
+#[test]
+fn payload_is_indexed_offchain() {
+	let mut ext = new_test_ext();
+	ext.execute_with(|| {
+		System::set_block_number(3);
+		System::set_extrinsic_index(1);
+		assert_ok!(TemplateModule::do_something(Origin::signed(1), 42));
+	});
+	ext.persist_offchain_overlay();
+
+	let key = TemplateModule::indexing_key(3, 1);
+	assert!(key.starts_with(crate::INDEXING_PREFIX));
+	assert_eq!(ext.offchain_db().get(&key), Some((1u64, 42u32).encode()));
+}
+
+#[test]
+fn stores_a_value_from_the_randomness_source() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(TemplateModule::store_random(Origin::signed(1)));
+
+		let (random, _) = TestRandomness::random(b"template::random");
+		let expected = u32::decode(&mut random.as_ref()).unwrap();
+		assert_eq!(TemplateModule::something(), Some(expected));
+		assert_eq!(TemplateModule::random_value(), expected);
+	});
+}
@@ -0,0 +1,150 @@
+//! Some configurable implementations as associated type for the runtime.
+
+use frame_support::traits::{
+	BalanceStatus, Currency, ExistenceRequirement, Get, NamedReservableCurrency,
+	ReservableCurrency, SignedImbalance, WithdrawReasons,
+};
+use sp_runtime::{DispatchError, DispatchResult};
+use sp_std::marker::PhantomData;
+
+use crate::{AccountId, FeeWhitelist};
+
+/// Exempts the signers `pallet_fee_whitelist` currently waives fees for.
+pub struct FeeWhitelistExemption;
+
+impl pallet_asset_tx_payment::FeeExemption<AccountId> for FeeWhitelistExemption {
+	fn is_exempt(who: &AccountId) -> bool {
+		FeeWhitelist::is_fee_waived(who)
+	}
+}
+
+/// A currency whose reserves are all held under the named reserve `Id` of `C`.
+///
+/// Pallets such as `pallet_indices` and `pallet_assets` only know about anonymous reserves. Handing
+/// them this instead of `Balances` files their deposits under their own identifier, so they can be
+/// told apart in `pallet_balances::Reserves`.
+pub struct NamedReserveCurrency<C, Id>(PhantomData<(C, Id)>);
+
+impl<AccountId, C, Id> Currency<AccountId> for NamedReserveCurrency<C, Id>
+where
+	C: Currency<AccountId>,
+{
+	type Balance = C::Balance;
+	type PositiveImbalance = C::PositiveImbalance;
+	type NegativeImbalance = C::NegativeImbalance;
+
+	fn total_balance(who: &AccountId) -> Self::Balance {
+		C::total_balance(who)
+	}
+
+	fn can_slash(who: &AccountId, value: Self::Balance) -> bool {
+		C::can_slash(who, value)
+	}
+
+	fn total_issuance() -> Self::Balance {
+		C::total_issuance()
+	}
+
+	fn minimum_balance() -> Self::Balance {
+		C::minimum_balance()
+	}
+
+	fn burn(amount: Self::Balance) -> Self::PositiveImbalance {
+		C::burn(amount)
+	}
+
+	fn issue(amount: Self::Balance) -> Self::NegativeImbalance {
+		C::issue(amount)
+	}
+
+	fn free_balance(who: &AccountId) -> Self::Balance {
+		C::free_balance(who)
+	}
+
+	fn ensure_can_withdraw(
+		who: &AccountId,
+		amount: Self::Balance,
+		reasons: WithdrawReasons,
+		new_balance: Self::Balance,
+	) -> DispatchResult {
+		C::ensure_can_withdraw(who, amount, reasons, new_balance)
+	}
+
+	fn transfer(
+		source: &AccountId,
+		dest: &AccountId,
+		value: Self::Balance,
+		existence_requirement: ExistenceRequirement,
+	) -> DispatchResult {
+		C::transfer(source, dest, value, existence_requirement)
+	}
+
+	fn slash(who: &AccountId, value: Self::Balance) -> (Self::NegativeImbalance, Self::Balance) {
+		C::slash(who, value)
+	}
+
+	fn deposit_into_existing(
+		who: &AccountId,
+		value: Self::Balance,
+	) -> Result<Self::PositiveImbalance, DispatchError> {
+		C::deposit_into_existing(who, value)
+	}
+
+	fn deposit_creating(who: &AccountId, value: Self::Balance) -> Self::PositiveImbalance {
+		C::deposit_creating(who, value)
+	}
+
+	fn withdraw(
+		who: &AccountId,
+		value: Self::Balance,
+		reasons: WithdrawReasons,
+		liveness: ExistenceRequirement,
+	) -> Result<Self::NegativeImbalance, DispatchError> {
+		C::withdraw(who, value, reasons, liveness)
+	}
+
+	fn make_free_balance_be(
+		who: &AccountId,
+		balance: Self::Balance,
+	) -> SignedImbalance<Self::Balance, Self::PositiveImbalance> {
+		C::make_free_balance_be(who, balance)
+	}
+}
+
+impl<AccountId, C, Id> ReservableCurrency<AccountId> for NamedReserveCurrency<C, Id>
+where
+	C: NamedReservableCurrency<AccountId>,
+	Id: Get<C::ReserveIdentifier>,
+{
+	fn can_reserve(who: &AccountId, value: Self::Balance) -> bool {
+		C::can_reserve(who, value)
+	}
+
+	fn slash_reserved(
+		who: &AccountId,
+		value: Self::Balance,
+	) -> (Self::NegativeImbalance, Self::Balance) {
+		C::slash_reserved_named(&Id::get(), who, value)
+	}
+
+	fn reserved_balance(who: &AccountId) -> Self::Balance {
+		C::reserved_balance_named(&Id::get(), who)
+	}
+
+	fn reserve(who: &AccountId, value: Self::Balance) -> DispatchResult {
+		C::reserve_named(&Id::get(), who, value)
+	}
+
+	fn unreserve(who: &AccountId, value: Self::Balance) -> Self::Balance {
+		C::unreserve_named(&Id::get(), who, value)
+	}
+
+	fn repatriate_reserved(
+		slashed: &AccountId,
+		beneficiary: &AccountId,
+		value: Self::Balance,
+		status: BalanceStatus,
+	) -> Result<Self::Balance, DispatchError> {
+		C::repatriate_reserved_named(&Id::get(), slashed, beneficiary, value, status)
+	}
+}
@@ -0,0 +1,195 @@
+//! Storage migrations run by `Executive` when the runtime is upgraded.
+//!
+//! [`Migrations`] lists every migration in the order it must run. Each one is wrapped in
+//! [`Versioned`], which only runs it when the on-chain [`StorageVersion`] of its pallet matches
+//! [`Migration::FROM`] and bumps it afterwards. A migration thus runs exactly once, even if the
+//! runtime is upgraded again before it is removed from the list.
+
+use crate::{
+	AccountId, Balance, Balances, Indices, IndicesReserveId, Nft, NftReserveId, ReserveIdentifier,
+	Runtime, Weight,
+};
+use frame_support::traits::{
+	Get, NamedReservableCurrency, OnRuntimeUpgrade, PalletInfoAccess, ReservableCurrency,
+	StorageVersion,
+};
+use sp_runtime::traits::Saturating;
+use sp_std::{collections::btree_map::BTreeMap, marker::PhantomData};
+
+/// Log target of all migrations.
+const LOG_TARGET: &str = "runtime::migrations";
+
+/// The migrations to run on the next runtime upgrade, in order.
+///
+/// Asset deposits are still reserved anonymously on chains that predate named reserves: the
+/// details recording them are private to `pallet_assets`, so they cannot be migrated from here.
+pub type Migrations = (Versioned<IndicesNamedReserves>, Versioned<NftNamedReserves>);
+
+type DbWeight = <Runtime as frame_system::Config>::DbWeight;
+
+/// A storage migration of a single pallet.
+pub trait Migration {
+	/// The migrated pallet, whose storage version scopes the migration.
+	type Pallet: PalletInfoAccess;
+	/// The storage version the migration applies to.
+	const FROM: u16;
+	/// The storage version after the migration.
+	const TO: u16;
+
+	/// Migrate the pallet's storage, returning the weight consumed.
+	fn migrate() -> Weight;
+}
+
+/// Runs `M` if the storage version of its pallet is [`Migration::FROM`].
+pub struct Versioned<M>(PhantomData<M>);
+
+impl<M: Migration> OnRuntimeUpgrade for Versioned<M> {
+	fn on_runtime_upgrade() -> Weight {
+		let pallet = M::Pallet::name();
+		let version = StorageVersion::get::<M::Pallet>();
+		if version != StorageVersion::new(M::FROM) {
+			log::info!(
+				target: LOG_TARGET,
+				"{}: storage is at {:?}, skipping migration from {} to {}",
+				pallet,
+				version,
+				M::FROM,
+				M::TO,
+			);
+			return DbWeight::get().reads(1)
+		}
+
+		log::info!(
+			target: LOG_TARGET,
+			"{}: migrating storage from version {} to {}",
+			pallet,
+			M::FROM,
+			M::TO,
+		);
+		let weight = M::migrate();
+		StorageVersion::new(M::TO).put::<M::Pallet>();
+		log::info!(target: LOG_TARGET, "{}: migration consumed {} weight", pallet, weight);
+
+		weight.saturating_add(DbWeight::get().reads_writes(1, 1))
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn pre_upgrade() -> Result<(), &'static str> {
+		let version = StorageVersion::get::<M::Pallet>();
+		if version != StorageVersion::new(M::FROM) && version != StorageVersion::new(M::TO) {
+			return Err("storage is at a version this migration does not know about")
+		}
+		Ok(())
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn post_upgrade() -> Result<(), &'static str> {
+		if StorageVersion::get::<M::Pallet>() != StorageVersion::new(M::TO) {
+			return Err("storage version was not bumped")
+		}
+		Ok(())
+	}
+}
+
+/// Hold the deposits `who` made to a pallet, which total `deposit` in the pallet's storage, under
+/// the named reserve `id`, returning the weight consumed.
+///
+/// Whatever part of `deposit` is already held under `id` was reserved after the pallet switched to
+/// named reserves and is left alone, as are any reserves `who` holds for other pallets. Chains
+/// started after the switch thus keep their deposits as they are when the migrations run.
+fn move_to_named_reserve(id: &ReserveIdentifier, who: &AccountId, deposit: Balance) -> Weight {
+	let amount = deposit.saturating_sub(Balances::reserved_balance_named(id, who));
+	if amount == 0 {
+		return DbWeight::get().reads(1)
+	}
+
+	let missing = Balances::unreserve(who, amount);
+	if missing != 0 {
+		log::warn!(
+			target: LOG_TARGET,
+			"{:?} has {} less reserved than its recorded deposits",
+			who,
+			missing,
+		);
+	}
+	// Whatever was just unreserved is free, so reserving it again can only fail if `who` already
+	// has `MaxReserves` named reserves.
+	if let Err(e) = Balances::reserve_named(id, who, amount.saturating_sub(missing)) {
+		log::error!(target: LOG_TARGET, "failed to reserve the deposits of {:?}: {:?}", who, e);
+	}
+
+	DbWeight::get().reads_writes(2, 2)
+}
+
+/// [`move_to_named_reserve`] the deposits of every account in `deposits`.
+fn move_all_to_named_reserve(
+	id: &ReserveIdentifier,
+	deposits: BTreeMap<AccountId, Balance>,
+) -> Weight {
+	deposits.iter().fold(0, |weight, (who, deposit)| {
+		weight.saturating_add(move_to_named_reserve(id, who, *deposit))
+	})
+}
+
+/// Hold the deposits of claimed indices under [`IndicesReserveId`].
+///
+/// Indices reserved their deposits anonymously before `pallet_indices` was given a
+/// `NamedReserveCurrency`, and could not release them afterwards.
+pub struct IndicesNamedReserves;
+
+impl Migration for IndicesNamedReserves {
+	type Pallet = Indices;
+	const FROM: u16 = 0;
+	const TO: u16 = 1;
+
+	fn migrate() -> Weight {
+		let mut deposits = BTreeMap::<AccountId, Balance>::new();
+		let mut reads = 0u64;
+		for (_, (who, deposit, _)) in pallet_indices::Accounts::<Runtime>::iter() {
+			let total = deposits.entry(who).or_default();
+			*total = total.saturating_add(deposit);
+			reads += 1;
+		}
+
+		move_all_to_named_reserve(&IndicesReserveId::get(), deposits)
+			.saturating_add(DbWeight::get().reads(reads))
+	}
+}
+
+/// Hold the collection, item and metadata deposits of `pallet_nft` under [`NftReserveId`].
+///
+/// All of them are reserved from the collection owner.
+pub struct NftNamedReserves;
+
+impl Migration for NftNamedReserves {
+	type Pallet = Nft;
+	const FROM: u16 = 0;
+	const TO: u16 = 1;
+
+	fn migrate() -> Weight {
+		let mut deposits = BTreeMap::<AccountId, Balance>::new();
+		let mut reads = 0u64;
+		for (collection, details) in pallet_nft::Collections::<Runtime>::iter() {
+			let metadata = pallet_nft::CollectionMetadataOf::<Runtime>::get(collection)
+				.map_or(0, |m| m.deposit);
+			let items = pallet_nft::Items::<Runtime>::iter_prefix_values(collection)
+				.inspect(|_| reads += 1)
+				.fold(0, |total: Balance, item| total.saturating_add(item.deposit));
+			let items_metadata =
+				pallet_nft::ItemMetadataOf::<Runtime>::iter_prefix_values(collection)
+					.inspect(|_| reads += 1)
+					.fold(0, |total: Balance, m| total.saturating_add(m.deposit));
+			reads += 2;
+
+			let total = deposits.entry(details.owner).or_default();
+			*total = total
+				.saturating_add(details.deposit)
+				.saturating_add(metadata)
+				.saturating_add(items)
+				.saturating_add(items_metadata);
+		}
+
+		move_all_to_named_reserve(&NftReserveId::get(), deposits)
+			.saturating_add(DbWeight::get().reads(reads))
+	}
+}
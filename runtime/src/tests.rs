@@ -0,0 +1,357 @@
+use crate::*;
+use frame_support::{
+	assert_noop, assert_ok,
+	traits::{
+		NamedReservableCurrency, OnInitialize, OnRuntimeUpgrade, ReservableCurrency, StorageVersion,
+	},
+};
+use frame_system::{LastRuntimeUpgrade, LastRuntimeUpgradeInfo};
+use pallet_vesting::VestingInfo;
+
+fn account(seed: u8) -> AccountId {
+	AccountId::from([seed; 32])
+}
+
+const ENDOWMENT: Balance = 1_000_000;
+
+/// A transfer signed by `who`, who chose to pay its fee in `asset_id`, if any. Its signature is
+/// not valid, which fee queries do not check.
+fn signed_transfer(who: AccountId, asset_id: Option<AssetId>) -> UncheckedExtrinsic {
+	let extra: SignedExtra = (
+		frame_system::CheckSpecVersion::new(),
+		frame_system::CheckTxVersion::new(),
+		frame_system::CheckGenesis::new(),
+		frame_system::CheckEra::from(generic::Era::Immortal),
+		frame_system::CheckNonce::from(0),
+		frame_system::CheckWeight::new(),
+		pallet_fee_whitelist::CheckFeeWhitelist::new(),
+		pallet_asset_tx_payment::ChargeAssetTxPayment::from(0, asset_id),
+	);
+	UncheckedExtrinsic::new_signed(
+		Call::Balances(BalancesCall::transfer(account(2).into(), 1)),
+		who.into(),
+		sp_core::sr25519::Signature::from_raw([0; 64]).into(),
+		extra,
+	)
+}
+
+/// The `partial_fee` that `payment_queryInfo` reports for `xt`.
+fn quoted_fee(xt: UncheckedExtrinsic) -> Balance {
+	use codec::Encode;
+	use pallet_transaction_payment_rpc_runtime_api::runtime_decl_for_TransactionPaymentApi::TransactionPaymentApi;
+
+	let len = xt.encode().len() as u32;
+	<Runtime as TransactionPaymentApi<Block, Balance>>::query_info(xt, len).partial_fee
+}
+
+/// The native fee of `xt`.
+fn native_fee(xt: UncheckedExtrinsic) -> Balance {
+	use codec::Encode;
+
+	let len = xt.encode().len() as u32;
+	TransactionPayment::query_info(xt, len).partial_fee
+}
+
+// Build genesis storage for the pallets exercised by these tests.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut t = frame_system::GenesisConfig::default().build_storage::<Runtime>().unwrap();
+	pallet_balances::GenesisConfig::<Runtime> {
+		balances: vec![(account(1), ENDOWMENT), (account(2), ENDOWMENT)],
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
+	pallet_vesting::GenesisConfig::<Runtime> {
+		// Alice's whole endowment vests linearly over 100 blocks from genesis.
+		vesting: vec![(account(1), 0, 100, 0)],
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
+
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
+
+#[test]
+fn genesis_vesting_locks_unvested_funds() {
+	new_test_ext().execute_with(|| {
+		let (alice, bob) = (account(1), account(2));
+		System::set_block_number(10);
+		assert_eq!(Vesting::vesting_balance(&alice), Some(ENDOWMENT * 90 / 100));
+
+		// The lock is only lowered once the schedule is vested.
+		assert_noop!(
+			Balances::transfer(Origin::signed(alice.clone()), bob.clone().into(), 1),
+			pallet_balances::Error::<Runtime>::LiquidityRestrictions
+		);
+		assert_ok!(Vesting::vest(Origin::signed(alice.clone())));
+
+		assert_noop!(
+			Balances::transfer(
+				Origin::signed(alice.clone()),
+				bob.clone().into(),
+				ENDOWMENT / 10 + 1
+			),
+			pallet_balances::Error::<Runtime>::LiquidityRestrictions
+		);
+		assert!(Balances::reserve(&alice, ENDOWMENT / 10 + 1).is_err());
+		assert_ok!(Balances::transfer(
+			Origin::signed(alice.clone()),
+			bob.clone().into(),
+			ENDOWMENT / 10
+		));
+	});
+}
+
+#[test]
+fn vested_transfer_locks_funds_at_destination() {
+	new_test_ext().execute_with(|| {
+		let (bob, charlie) = (account(2), account(3));
+		let schedule = VestingInfo {
+			locked: MinVestedTransfer::get() * 2,
+			per_block: MinVestedTransfer::get() / 10,
+			starting_block: 5,
+		};
+		assert_ok!(Vesting::vested_transfer(
+			Origin::signed(bob.clone()),
+			charlie.clone().into(),
+			schedule
+		));
+		assert_eq!(Balances::free_balance(&charlie), MinVestedTransfer::get() * 2);
+
+		// Nothing has vested before the starting block.
+		assert_noop!(
+			Balances::transfer(Origin::signed(charlie.clone()), bob.clone().into(), 1),
+			pallet_balances::Error::<Runtime>::LiquidityRestrictions
+		);
+
+		System::set_block_number(15);
+		assert_ok!(Vesting::vest(Origin::signed(charlie.clone())));
+		assert_noop!(
+			Balances::transfer(
+				Origin::signed(charlie.clone()),
+				bob.clone().into(),
+				MinVestedTransfer::get() + 1
+			),
+			pallet_balances::Error::<Runtime>::LiquidityRestrictions
+		);
+		assert_ok!(Balances::transfer(
+			Origin::signed(charlie),
+			bob.clone().into(),
+			MinVestedTransfer::get()
+		));
+	});
+}
+
+#[test]
+fn vested_transfer_below_minimum_is_rejected() {
+	new_test_ext().execute_with(|| {
+		let schedule =
+			VestingInfo { locked: MinVestedTransfer::get() - 1, per_block: 1, starting_block: 5 };
+		assert_noop!(
+			Vesting::vested_transfer(Origin::signed(account(2)), account(3).into(), schedule),
+			pallet_vesting::Error::<Runtime>::AmountLow
+		);
+	});
+}
+
+#[test]
+fn claimed_index_can_be_used_as_address() {
+	new_test_ext().execute_with(|| {
+		let (bob, charlie) = (account(2), account(3));
+		assert_ok!(Indices::claim(Origin::signed(bob.clone()), 7));
+		assert_eq!(Balances::reserved_balance(&bob), IndexDeposit::get());
+
+		assert_ok!(Balances::transfer(
+			Origin::signed(bob.clone()),
+			charlie.clone().into(),
+			ENDOWMENT / 2
+		));
+		assert_ok!(Balances::transfer(Origin::signed(charlie), Address::Index(7), 1_000));
+		assert_eq!(Balances::free_balance(&bob), ENDOWMENT / 2 - IndexDeposit::get() + 1_000);
+	});
+}
+
+#[test]
+fn frozen_index_cannot_be_transferred() {
+	new_test_ext().execute_with(|| {
+		let (bob, charlie) = (account(2), account(3));
+		assert_ok!(Indices::claim(Origin::signed(bob.clone()), 7));
+		assert_ok!(Indices::freeze(Origin::signed(bob.clone()), 7));
+
+		assert_noop!(
+			Indices::transfer(Origin::signed(bob), charlie, 7),
+			pallet_indices::Error::<Runtime>::Permanent
+		);
+	});
+}
+
+#[test]
+fn deposits_are_held_under_named_reserves() {
+	new_test_ext().execute_with(|| {
+		let bob = account(2);
+		assert_ok!(Indices::claim(Origin::signed(bob.clone()), 7));
+		assert_ok!(Nft::create_collection(Origin::signed(bob.clone())));
+
+		assert_eq!(
+			Balances::reserved_balance_named(&IndicesReserveId::get(), &bob),
+			IndexDeposit::get()
+		);
+		assert_eq!(
+			Balances::reserved_balance_named(&NftReserveId::get(), &bob),
+			CollectionDeposit::get()
+		);
+		assert_eq!(
+			Balances::reserved_balance(&bob),
+			IndexDeposit::get() + CollectionDeposit::get()
+		);
+	});
+}
+
+#[test]
+fn migrations_run_once_after_spec_version_bump() {
+	new_test_ext().execute_with(|| {
+		let bob = account(2);
+		// An index claimed while deposits were still reserved anonymously, next to an anonymous
+		// reserve of another pallet.
+		assert_ok!(Balances::reserve(&bob, IndexDeposit::get() + 10));
+		pallet_indices::Accounts::<Runtime>::insert(7, (bob.clone(), IndexDeposit::get(), false));
+		// The chain last upgraded to the previous spec version.
+		LastRuntimeUpgrade::<Runtime>::put(LastRuntimeUpgradeInfo {
+			spec_version: (VERSION.spec_version - 1).into(),
+			spec_name: VERSION.spec_name.clone(),
+		});
+
+		Executive::initialize_block(&Header::new(
+			2,
+			Default::default(),
+			Default::default(),
+			Default::default(),
+			Default::default(),
+		));
+
+		assert!(!LastRuntimeUpgrade::<Runtime>::get().unwrap().was_upgraded(&VERSION));
+		assert_eq!(StorageVersion::get::<Indices>(), StorageVersion::new(1));
+		assert_eq!(StorageVersion::get::<Nft>(), StorageVersion::new(1));
+		// Only the deposit recorded by `pallet_indices` was moved.
+		assert_eq!(
+			Balances::reserved_balance_named(&IndicesReserveId::get(), &bob),
+			IndexDeposit::get()
+		);
+		assert_eq!(Balances::reserved_balance(&bob), IndexDeposit::get() + 10);
+
+		// Running the pipeline again only reads the storage versions.
+		assert_eq!(
+			<migrations::Migrations as OnRuntimeUpgrade>::on_runtime_upgrade(),
+			<Runtime as frame_system::Config>::DbWeight::get().reads(2)
+		);
+		assert_eq!(Balances::reserved_balance(&bob), IndexDeposit::get() + 10);
+	});
+}
+
+#[test]
+fn migrations_leave_named_reserves_of_fresh_chains_alone() {
+	new_test_ext().execute_with(|| {
+		let bob = account(2);
+		// Deposits taken since genesis are already named, but `pallet_indices` declares no storage
+		// version, so its migration runs on fresh chains too.
+		assert_ok!(Indices::claim(Origin::signed(bob.clone()), 7));
+		assert_ok!(Nft::create_collection(Origin::signed(bob.clone())));
+		assert_eq!(StorageVersion::get::<Indices>(), StorageVersion::new(0));
+
+		<migrations::Migrations as OnRuntimeUpgrade>::on_runtime_upgrade();
+
+		assert_eq!(
+			Balances::reserved_balance_named(&IndicesReserveId::get(), &bob),
+			IndexDeposit::get()
+		);
+		assert_eq!(
+			Balances::reserved_balance_named(&NftReserveId::get(), &bob),
+			CollectionDeposit::get()
+		);
+		assert_eq!(
+			Balances::reserved_balance(&bob),
+			IndexDeposit::get() + CollectionDeposit::get()
+		);
+	});
+}
+
+#[test]
+fn every_block_is_committed_to_the_mmr() {
+	new_test_ext().execute_with(|| {
+		Mmr::on_initialize(1);
+		let root = Mmr::mmr_root_hash();
+		Mmr::on_initialize(2);
+
+		assert_eq!(Mmr::mmr_leaves(), 2);
+		assert_ne!(Mmr::mmr_root_hash(), root);
+	});
+}
+
+#[test]
+fn stash_can_bond_and_validate() {
+	new_test_ext().execute_with(|| {
+		let (stash, controller) = (account(2), account(3));
+		assert_ok!(Staking::bond(
+			Origin::signed(stash.clone()),
+			controller.clone().into(),
+			ENDOWMENT / 2,
+			pallet_staking::RewardDestination::Staked
+		));
+		assert_eq!(Staking::bonded(&stash), Some(controller.clone()));
+		// Bonded funds are locked, not reserved.
+		assert_eq!(Balances::usable_balance(&stash), ENDOWMENT / 2);
+
+		assert_ok!(Staking::validate(
+			Origin::signed(controller),
+			pallet_staking::ValidatorPrefs::default()
+		));
+		assert!(pallet_staking::Validators::<Runtime>::contains_key(&stash));
+	});
+}
+
+#[test]
+fn block_authors_are_counted_as_online() {
+	new_test_ext().execute_with(|| {
+		let author = account(2);
+		<ImOnline as pallet_authorship::EventHandler<_, _>>::note_author(author.clone());
+		assert_eq!(ImOnline::authored_blocks(Session::current_index(), &author), 1);
+	});
+}
+
+#[test]
+fn template_pallet_draws_on_the_runtime_randomness() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(TemplateModule::store_random(Origin::signed(account(1))));
+
+		let (random, _) = RandomnessSource::random(b"template::random");
+		let expected = <u32 as codec::Decode>::decode(&mut random.as_ref()).unwrap();
+		assert_eq!(TemplateModule::something(), Some(expected));
+	});
+}
+
+#[test]
+fn whitelisted_signers_are_quoted_no_fee() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(FeeWhitelist::add_account(Origin::root(), account(1)));
+
+		assert_eq!(quoted_fee(signed_transfer(account(1), None)), 0);
+		assert!(quoted_fee(signed_transfer(account(2), None)) > 0);
+	});
+}
+
+#[test]
+fn fees_are_quoted_in_the_chosen_asset() {
+	use sp_runtime::{FixedPointNumber, FixedU128};
+
+	new_test_ext().execute_with(|| {
+		let rate = FixedU128::saturating_from_integer(2);
+		assert_ok!(AssetTxPayment::set_conversion_rate(Origin::root(), 1, rate));
+
+		let xt = signed_transfer(account(2), Some(1));
+		assert_eq!(quoted_fee(xt.clone()), 2 * native_fee(xt));
+		// Assets without a conversion rate are quoted in the native currency.
+		let xt = signed_transfer(account(2), Some(2));
+		assert_eq!(quoted_fee(xt.clone()), native_fee(xt));
+	});
+}